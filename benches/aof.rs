@@ -0,0 +1,94 @@
+//! 对比两种读取AOF命令的方式:
+//! - `decode_resp`: 每个参数单独分配`Vec<u8>`，命令名转换为新的`String`
+//! - `command_frame`: 复用同一个`CommandFrame`，参数为其缓冲区中的切片
+//!
+//! 运行: `cargo bench --bench aof`
+
+use std::io::{BufReader, Cursor};
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use redis_event::cmd::RawCommand;
+use redis_event::resp::{CommandFrame, Resp, RespDecode};
+use redis_event::{Event, EventHandler};
+
+const COMMANDS: usize = 10_000;
+
+struct CountHandler(usize);
+
+impl EventHandler for CountHandler {
+    fn handle(&mut self, event: Event) {
+        if let Event::AOF(_) = event {
+            self.0 += 1;
+        }
+    }
+}
+
+fn encode(args: &[&[u8]], output: &mut Vec<u8>) {
+    output.extend_from_slice(format!("*{}\r\n", args.len()).as_bytes());
+    for arg in args {
+        output.extend_from_slice(format!("${}\r\n", arg.len()).as_bytes());
+        output.extend_from_slice(arg);
+        output.extend_from_slice(b"\r\n");
+    }
+}
+
+/// 模拟master传播的命令流，包含不同大小写的命令名
+fn commands() -> Vec<u8> {
+    let value = vec![b'v'; 64];
+    let mut output = Vec::new();
+    for i in 0..COMMANDS {
+        let key = format!("key:{}", i);
+        match i % 4 {
+            0 => encode(&[b"SET", key.as_bytes(), &value], &mut output),
+            1 => encode(&[b"hset", key.as_bytes(), b"field", &value], &mut output),
+            2 => encode(&[b"Sadd", key.as_bytes(), b"a", b"b", b"c"], &mut output),
+            _ => encode(&[b"DEL", key.as_bytes()], &mut output),
+        }
+    }
+    output
+}
+
+fn decode_resp(input: &[u8]) -> usize {
+    let mut reader = BufReader::new(Cursor::new(input));
+    let mut handler = CountHandler(0);
+    for _ in 0..COMMANDS {
+        if let Ok(Resp::Array(array)) = reader.decode_resp() {
+            let mut args: Vec<Vec<u8>> = array
+                .into_iter()
+                .map(|arg| match arg {
+                    Resp::BulkBytes(bytes) => bytes,
+                    _ => panic!("Expected BulkString response"),
+                })
+                .collect();
+            let name = String::from_utf8_lossy(&args.remove(0)).to_uppercase();
+            RawCommand { name, args }.dispatch(&mut handler);
+        }
+    }
+    handler.0
+}
+
+fn command_frame(input: &[u8]) -> usize {
+    let mut reader = BufReader::new(Cursor::new(input));
+    let mut handler = CountHandler(0);
+    let mut frame = CommandFrame::new();
+    for _ in 0..COMMANDS {
+        frame.read_from(&mut reader).unwrap();
+        frame.dispatch(&mut handler);
+    }
+    handler.0
+}
+
+fn bench_aof(c: &mut Criterion) {
+    let input = commands();
+    assert_eq!(COMMANDS, decode_resp(&input));
+    assert_eq!(COMMANDS, command_frame(&input));
+
+    let mut group = c.benchmark_group("aof");
+    group.throughput(Throughput::Elements(COMMANDS as u64));
+    group.bench_function("decode_resp", |b| b.iter(|| decode_resp(&input)));
+    group.bench_function("command_frame", |b| b.iter(|| command_frame(&input)));
+    group.finish();
+}
+
+criterion_group!(benches, bench_aof);
+criterion_main!(benches);
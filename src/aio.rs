@@ -0,0 +1,682 @@
+/*!
+基于tokio的异步Listener，需开启`async-tokio` feature，TLS连接另需开启`async-tokio-native-tls`或`async-tokio-rustls`
+
+[`AsyncListener`]在tokio运行时中完成连接、认证及replication握手，之后的同步过程在后台任务中进行，
+事件以[`EventStream`]的形式交给调用方。`EventStream`内部是一个有界的channel，调用方处理得慢时，
+后台任务将停止从连接中读取数据，而不是无限制地占用内存。
+
+RDB的解析复用同步版本的解析器，在`spawn_blocking`的线程中驱动异步的连接。
+
+# 示例
+
+```no_run
+use redis_event::aio::AsyncListener;
+use redis_event::config::Config;
+use redis_event::OwnedEvent;
+
+async fn run(config: Config) -> std::io::Result<()> {
+    let mut stream = AsyncListener::new(config).start().await?;
+    while let Some(event) = stream.next().await {
+        match event? {
+            OwnedEvent::RDB(obj) => println!("{:?}", obj),
+            OwnedEvent::AOF(cmd) => println!("{:?}", cmd),
+        }
+    }
+    let stopped = stream.stop().await?;
+    println!("{} {}", stopped.repl_id, stopped.repl_offset);
+    Ok(())
+}
+```
+
+[`AsyncListener`]: struct.AsyncListener.html
+[`EventStream`]: struct.EventStream.html
+*/
+use std::future::Future;
+use std::io::{Error, ErrorKind, Read, Result};
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use log::{error, info, warn};
+use socket2::SockRef;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader, ReadBuf, ReadHalf, WriteHalf};
+use tokio::net::TcpStream;
+use tokio::runtime::Handle;
+use tokio::sync::{mpsc, watch, Mutex};
+use tokio::task::JoinHandle;
+
+use crate::cmd::RawCommand;
+use crate::config::{Config, RejectedOption};
+use crate::io::{self, encode, format_addr};
+use crate::listener::{handshake_commands, is_getack, psync_args, warn_snapshot_fallback, UNIX_SOCKET_PREFIX};
+use crate::rdb::{DefaultRDBParser, OwnedObject};
+use crate::resp::{AsyncRespDecode, Resp, Type};
+use crate::{tls, Event, EventHandler, OwnedEvent, RDBParser, Stopped};
+
+/// 异步监听单个Redis实例的事件
+pub struct AsyncListener {
+    config: Config,
+    buffer: usize,
+}
+
+impl AsyncListener {
+    /// 创建`AsyncListener`
+    pub fn new(config: Config) -> AsyncListener {
+        AsyncListener { config, buffer: 1024 }
+    }
+
+    /// 设置缓存的事件数量，缓存已满时将暂停读取，默认为1024
+    pub fn with_buffer(&mut self, capacity: usize) {
+        self.buffer = capacity.max(1);
+    }
+
+    /// 连接Redis并完成握手，之后在后台任务中进行同步
+    ///
+    /// 必须在tokio运行时中调用，连接、认证失败时返回错误，同步过程中的错误将作为`EventStream`的最后一个元素返回
+    pub async fn start(self) -> Result<EventStream> {
+        let (conn, local_addr) = connect(&self.config).await?;
+        let (reader, writer) = tokio::io::split(conn);
+        let (stop_tx, stop_rx) = watch::channel(false);
+        let (events_tx, events_rx) = mpsc::channel(self.buffer);
+        let mut replication = Replication {
+            repl_offset: Arc::new(AtomicI64::new(self.config.repl_offset)),
+            config: self.config,
+            reader: Some(BufReader::new(reader)),
+            writer: Arc::new(Mutex::new(writer)),
+            events: events_tx,
+            stop: stop_rx,
+        };
+        replication.auth().await?;
+        let rejected_options = replication.send_replica_info(local_addr).await?;
+        let task = tokio::spawn(replication.run());
+        Ok(EventStream {
+            events: events_rx,
+            stop: stop_tx,
+            task,
+            rejected_options,
+        })
+    }
+}
+
+/// `AsyncListener`产生的事件流
+///
+/// 实现了`futures_core::Stream`，也可以直接调用[`next`]。丢弃`EventStream`时后台任务将随之中止，
+/// 需要获取中止时的进度时，调用[`stop`]
+///
+/// [`next`]: struct.EventStream.html#method.next
+/// [`stop`]: struct.EventStream.html#method.stop
+pub struct EventStream {
+    events: mpsc::Receiver<Result<OwnedEvent>>,
+    stop: watch::Sender<bool>,
+    task: JoinHandle<Stopped>,
+    rejected_options: Vec<RejectedOption>,
+}
+
+impl EventStream {
+    /// 握手中被master拒绝的选项，如旧版本Redis不支持的`REPLCONF`选项
+    pub fn rejected_options(&self) -> &[RejectedOption] {
+        &self.rejected_options
+    }
+
+    /// 获取下一个事件，同步结束(`is_aof`为false时RDB处理完毕，或出错)之后返回None
+    pub async fn next(&mut self) -> Option<Result<OwnedEvent>> {
+        self.events.recv().await
+    }
+
+    /// 中止同步，返回中止时的replication id及offset
+    ///
+    /// 同步因出错而结束时，返回的是出错之前已确认的进度，可用于重新连接
+    pub async fn stop(self) -> Result<Stopped> {
+        let _ = self.stop.send(true);
+        drop(self.events);
+        self.task
+            .await
+            .map_err(|err| Error::new(ErrorKind::Interrupted, err.to_string()))
+    }
+}
+
+impl futures_core::Stream for EventStream {
+    type Item = Result<OwnedEvent>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.events.poll_recv(cx)
+    }
+}
+
+/// 异步的连接，TCP、TLS及unix socket均实现此接口
+trait Connection: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> Connection for T {}
+
+type Reader = BufReader<ReadHalf<Box<dyn Connection>>>;
+type Writer = Arc<Mutex<WriteHalf<Box<dyn Connection>>>>;
+
+async fn connect(config: &Config) -> Result<(Box<dyn Connection>, Option<SocketAddr>)> {
+    if let Some(path) = config.host.strip_prefix(UNIX_SOCKET_PREFIX) {
+        return connect_unix(config, path).await;
+    }
+    let addr = format_addr(&config.host, config.port);
+    let stream = connect_tcp(config).await?;
+    io::set_tcp_options(SockRef::from(&stream), config)?;
+    let local_addr = stream.local_addr().ok();
+    let conn: Box<dyn Connection> = if config.is_tls_enabled {
+        connect_tls(config, stream).await?
+    } else {
+        Box::new(stream)
+    };
+    info!("Connected to server {}", &addr);
+    Ok((conn, local_addr))
+}
+
+/// 依次尝试host解析出的每个地址，每个地址分别计算连接超时
+async fn connect_tcp(config: &Config) -> Result<TcpStream> {
+    let mut last_err = Error::new(
+        ErrorKind::NotFound,
+        format!("Unresolved address {}", format_addr(&config.host, config.port)),
+    );
+    for addr in tokio::net::lookup_host((config.host.as_str(), config.port)).await? {
+        match with_timeout(config.connect_timeout, TcpStream::connect(addr)).await {
+            Ok(stream) => return Ok(stream),
+            Err(err) => {
+                warn!("连接 {} 失败: {}", addr, err);
+                last_err = err;
+            }
+        }
+    }
+    Err(last_err)
+}
+
+#[cfg_attr(not(any(feature = "native-tls", feature = "rustls")), allow(unused_variables))]
+async fn connect_tls(config: &Config, stream: TcpStream) -> Result<Box<dyn Connection>> {
+    match tls::backend(config)? {
+        #[cfg(feature = "native-tls")]
+        tls::Backend::NativeTls => native_tls_stream(config, stream).await,
+        #[cfg(feature = "rustls")]
+        tls::Backend::Rustls => rustls_stream(config, stream).await,
+    }
+}
+
+#[cfg(feature = "async-tokio-native-tls")]
+async fn native_tls_stream(config: &Config, stream: TcpStream) -> Result<Box<dyn Connection>> {
+    let connector = tokio_native_tls::TlsConnector::from(tls::native_tls_connector(config)?);
+    let tls_stream = connector
+        .connect(tls::server_name(config), stream)
+        .await
+        .map_err(|err| Error::new(ErrorKind::ConnectionRefused, err))?;
+    Ok(Box::new(tls_stream))
+}
+
+#[cfg(all(feature = "native-tls", not(feature = "async-tokio-native-tls")))]
+async fn native_tls_stream(_: &Config, _: TcpStream) -> Result<Box<dyn Connection>> {
+    Err(Error::new(
+        ErrorKind::Unsupported,
+        "TLS with native-tls requires the async-tokio-native-tls feature",
+    ))
+}
+
+#[cfg(feature = "async-tokio-rustls")]
+async fn rustls_stream(config: &Config, stream: TcpStream) -> Result<Box<dyn Connection>> {
+    let connector = tokio_rustls::TlsConnector::from(tls::rustls_config(config)?);
+    let tls_stream = connector.connect(tls::rustls_server_name(config)?, stream).await?;
+    Ok(Box::new(tls_stream))
+}
+
+#[cfg(all(feature = "rustls", not(feature = "async-tokio-rustls")))]
+async fn rustls_stream(_: &Config, _: TcpStream) -> Result<Box<dyn Connection>> {
+    Err(Error::new(
+        ErrorKind::Unsupported,
+        "TLS with rustls requires the async-tokio-rustls feature",
+    ))
+}
+
+#[cfg(unix)]
+async fn connect_unix(config: &Config, path: &str) -> Result<(Box<dyn Connection>, Option<SocketAddr>)> {
+    if config.is_tls_enabled {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "TLS is not supported over unix socket",
+        ));
+    }
+    let stream = tokio::net::UnixStream::connect(path).await?;
+    info!("Connected to server {}", path);
+    Ok((Box::new(stream), None))
+}
+
+#[cfg(not(unix))]
+async fn connect_unix(_: &Config, path: &str) -> Result<(Box<dyn Connection>, Option<SocketAddr>)> {
+    Err(Error::new(
+        ErrorKind::Unsupported,
+        format!("unix socket is not supported on this platform: {}", path),
+    ))
+}
+
+/// 在后台任务中进行同步的状态
+struct Replication {
+    config: Config,
+    // 解析RDB时交给阻塞线程，解析完毕之后归还
+    reader: Option<Reader>,
+    writer: Writer,
+    events: mpsc::Sender<Result<OwnedEvent>>,
+    stop: watch::Receiver<bool>,
+    repl_offset: Arc<AtomicI64>,
+}
+
+impl Replication {
+    async fn run(mut self) -> Stopped {
+        if let Err(err) = self.replicate().await {
+            if self.is_stopped() {
+                info!("Listener stopped: {}", err);
+            } else {
+                error!("{}", err);
+                let _ = self.events.send(Err(err)).await;
+            }
+        }
+        Stopped {
+            repl_id: self.config.repl_id,
+            repl_offset: self.config.repl_offset,
+        }
+    }
+
+    /// 调用了`EventStream::stop`，或者`EventStream`已被丢弃
+    fn is_stopped(&self) -> bool {
+        *self.stop.borrow() || self.stop.has_changed().is_err() || self.events.is_closed()
+    }
+
+    fn reader(&mut self) -> &mut Reader {
+        self.reader.as_mut().unwrap()
+    }
+
+    async fn decode_resp(&mut self) -> Result<Resp> {
+        let timeout = self.config.read_timeout;
+        with_timeout(timeout, self.reader().decode_resp()).await
+    }
+
+    /// 如果有设置密码，将尝试使用此密码进行认证
+    async fn auth(&mut self) -> Result<()> {
+        if !self.config.password.is_empty() {
+            let mut args = Vec::with_capacity(2);
+            if !self.config.username.is_empty() {
+                args.push(self.config.username.as_bytes());
+            }
+            args.push(self.config.password.as_bytes());
+            send(&self.writer, self.config.write_timeout, b"AUTH", &args).await?;
+            if let Resp::Error(err) = self.decode_resp().await? {
+                return Err(Error::new(ErrorKind::PermissionDenied, err));
+            }
+        }
+        Ok(())
+    }
+
+    /// 发送replica相关信息到redis，此端口展现在`info replication`中，返回被master拒绝的选项
+    async fn send_replica_info(&mut self, local_addr: Option<SocketAddr>) -> Result<Vec<RejectedOption>> {
+        let mut rejected_options = Vec::new();
+        for command in handshake_commands(&self.config, local_addr) {
+            let line = command.join(" ");
+            info!("{}", line);
+            let args: Vec<&[u8]> = command[1..].iter().map(|arg| arg.as_bytes()).collect();
+            send(&self.writer, self.config.write_timeout, command[0].as_bytes(), &args).await?;
+            if let Some(error) = self.reply().await? {
+                rejected_options.push(RejectedOption { command: line, error });
+            }
+        }
+        if self.config.is_snapshot {
+            warn_snapshot_fallback(&rejected_options);
+        }
+        Ok(rejected_options)
+    }
+
+    /// 读取握手命令的响应，master拒绝时返回错误信息，认证相关的错误将中断握手
+    async fn reply(&mut self) -> Result<Option<String>> {
+        match self.decode_resp().await? {
+            Resp::String(str) => info!("{}", str),
+            Resp::Error(err) => {
+                warn!("{}", &err);
+                if (err.contains("NOAUTH") || err.contains("NOPERM"))
+                    && !err.contains("no password")
+                    && !err.contains("Unrecognized REPLCONF option")
+                {
+                    return Err(Error::new(ErrorKind::InvalidData, err));
+                }
+                return Ok(Some(err));
+            }
+            resp => {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("Unexpected response: {:?}", resp),
+                ))
+            }
+        }
+        Ok(None)
+    }
+
+    async fn replicate(&mut self) -> Result<()> {
+        let mode = loop {
+            match self.start_sync().await? {
+                Mode::Wait => {
+                    let mut stop = self.stop.clone();
+                    tokio::select! {
+                        _ = tokio::time::sleep(Duration::from_secs(5)) => {}
+                        _ = stopped(&mut stop) => return Ok(()),
+                    }
+                }
+                mode => break mode,
+            }
+        };
+        if self.config.is_snapshot && !self.is_stopped() {
+            info!("快照已接收完毕, 关闭连接");
+            if let Err(err) = self.writer.lock().await.shutdown().await {
+                warn!("关闭连接失败: {}", err);
+            }
+            return Ok(());
+        }
+        if !self.config.is_aof || self.is_stopped() {
+            return Ok(());
+        }
+        let heartbeat = match mode {
+            Mode::PSync => Some(self.start_heartbeat()),
+            _ => None,
+        };
+        let result = self.receive_aof(&mode).await;
+        if let Some(heartbeat) = heartbeat {
+            info!("Cancel heartbeat");
+            heartbeat.abort();
+        }
+        result
+    }
+
+    /// 开启replication
+    /// 默认使用PSYNC命令，若不支持PSYNC则尝试使用SYNC命令
+    async fn start_sync(&mut self) -> Result<Mode> {
+        let (repl_id, offset) = psync_args(&self.config);
+        send(
+            &self.writer,
+            self.config.write_timeout,
+            b"PSYNC",
+            &[repl_id.as_bytes(), offset.as_bytes()],
+        )
+        .await?;
+
+        let (mode, repl_id, repl_offset) = match self.decode_resp().await? {
+            Resp::String(resp) if resp.starts_with("FULLRESYNC") => {
+                info!("{}", resp);
+                let mut iter = resp.split_whitespace().skip(1);
+                let repl_id = iter.next().map(str::to_owned);
+                let repl_offset = iter.next().and_then(|offset| offset.parse::<i64>().ok());
+                match (repl_id, repl_offset) {
+                    (Some(repl_id), Some(repl_offset)) => (Mode::PSync, repl_id, repl_offset),
+                    _ => return Err(Error::new(ErrorKind::InvalidData, resp)),
+                }
+            }
+            Resp::String(resp) if resp.starts_with("CONTINUE") => {
+                info!("{}", resp);
+                if let Some(repl_id) = resp.split_whitespace().nth(1) {
+                    self.config.repl_id = repl_id.to_owned();
+                }
+                info!("PSYNC进度恢复");
+                return Ok(Mode::PSync);
+            }
+            Resp::String(resp) if resp.starts_with("DUALCHANNELSYNC") => {
+                return Err(Error::new(
+                    ErrorKind::Unsupported,
+                    "dual-channel replication is not supported by AsyncListener",
+                ))
+            }
+            Resp::String(resp) if resp.starts_with("NOMASTERLINK") || resp.starts_with("LOADING") => {
+                info!("{}", resp);
+                return Ok(Mode::Wait);
+            }
+            Resp::Error(err) if err.starts_with("ERR unknown command") => {
+                info!("源Redis不支持PSYNC命令, 使用SYNC命令再次进行尝试");
+                send(&self.writer, self.config.write_timeout, b"SYNC", &[]).await?;
+                (Mode::Sync, self.config.repl_id.clone(), self.config.repl_offset)
+            }
+            resp => {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("Unexpected response: {:?}", resp),
+                ))
+            }
+        };
+
+        info!("等待Redis dump完成...");
+        let (length, eof_mark) = self.rdb_length().await?;
+        if length != -1 {
+            info!("Full Sync, size: {}bytes", length);
+        } else {
+            info!("Disk-less replication.");
+        }
+        if length != -1 && self.config.is_discard_rdb {
+            info!("跳过RDB不进行处理");
+            let mut stop = self.stop.clone();
+            let mut rdb = self.reader().take(length as u64);
+            let mut sink = tokio::io::sink();
+            tokio::select! {
+                result = tokio::io::copy(&mut rdb, &mut sink) => { result?; }
+                _ = stopped(&mut stop) => return Ok(mode),
+            }
+        } else {
+            self.parse_rdb(length, eof_mark).await?;
+        }
+        // FULLRESYNC返回的replication id及offset要等RDB接收完毕之后才生效
+        if !self.is_stopped() {
+            self.config.repl_id = repl_id;
+            self.config.repl_offset = repl_offset;
+            self.repl_offset.store(repl_offset, Ordering::SeqCst);
+        }
+        Ok(mode)
+    }
+
+    /// 读取RDB的长度，无盘复制时返回-1及EOF标记
+    async fn rdb_length(&mut self) -> Result<(i64, Option<Vec<u8>>)> {
+        let reader = self.reader();
+        match reader.decode_type().await? {
+            Type::BulkString => io::parse_rdb_length(&reader.decode_string().await?),
+            _ => Err(Error::new(ErrorKind::InvalidData, "Expect BulkString response")),
+        }
+    }
+
+    /// 在阻塞线程中运行RDB解析器，解析出的数据通过channel发送
+    async fn parse_rdb(&mut self, length: i64, eof_mark: Option<Vec<u8>>) -> Result<()> {
+        let running = Arc::new(AtomicBool::new(true));
+        let mut input = BlockingReader {
+            inner: self.reader.take().unwrap(),
+            handle: Handle::current(),
+            stop: self.stop.clone(),
+            running: Arc::clone(&running),
+        };
+        let mut handler = ChannelHandler {
+            events: self.events.clone(),
+            running: Arc::clone(&running),
+        };
+        let checksum = self.config.rdb_checksum;
+        let task = tokio::task::spawn_blocking(move || {
+            let mut parser = DefaultRDBParser {
+                running,
+                module_parser: None,
+                checksum,
+                progress_handler: None,
+            };
+            let mut result = parser.parse(&mut input, length, &mut handler);
+            if let (Ok(_), Some(eof_mark)) = (&result, &eof_mark) {
+                result = io::verify_eof_mark(&mut input, eof_mark);
+            }
+            (input.inner, result)
+        });
+        let (reader, result) = task
+            .await
+            .map_err(|err| Error::new(ErrorKind::Interrupted, err.to_string()))?;
+        self.reader = Some(reader);
+        result
+    }
+
+    /// 以tokio interval定时向master汇报offset
+    fn start_heartbeat(&self) -> JoinHandle<()> {
+        info!("Start heartbeat");
+        let writer = Arc::clone(&self.writer);
+        let repl_offset = Arc::clone(&self.repl_offset);
+        let write_timeout = self.config.write_timeout;
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(1));
+            loop {
+                interval.tick().await;
+                let offset = repl_offset.load(Ordering::Relaxed).to_string();
+                if let Err(error) = send(&writer, write_timeout, b"REPLCONF", &[b"ACK", offset.as_bytes()]).await {
+                    error!("heartbeat error: {}", error);
+                }
+            }
+        })
+    }
+
+    async fn receive_aof(&mut self, mode: &Mode) -> Result<()> {
+        let mut stop = self.stop.clone();
+        let timeout = self.config.read_timeout;
+        loop {
+            let mut counter = CountReader::new(self.reader.as_mut().unwrap());
+            let resp = tokio::select! {
+                resp = with_timeout(timeout, counter.decode_resp()) => resp?,
+                _ = stopped(&mut stop) => return Ok(()),
+            };
+            let size = counter.len;
+            let args = match resp {
+                Resp::Array(array) => array
+                    .into_iter()
+                    .map(|x| match x {
+                        Resp::BulkBytes(bytes) => Ok(bytes),
+                        _ => Err(Error::new(ErrorKind::InvalidData, "Expected BulkString response")),
+                    })
+                    .collect::<Result<Vec<Vec<u8>>>>()?,
+                _ => return Err(Error::new(ErrorKind::InvalidData, "Expected array response")),
+            };
+            if is_getack(args.iter().map(Vec::as_slice)) {
+                // ACK中的offset不包含GETACK命令本身，与Redis replica的行为保持一致
+                let offset = self.config.repl_offset.to_string();
+                send(
+                    &self.writer,
+                    self.config.write_timeout,
+                    b"REPLCONF",
+                    &[b"ACK", offset.as_bytes()],
+                )
+                .await?;
+            }
+            if let Some(cmd) = RawCommand::from_args(args) {
+                if cmd.name != "PING" && cmd.name != "REPLCONF" {
+                    tokio::select! {
+                        result = self.events.send(Ok(OwnedEvent::AOF(cmd))) => {
+                            if result.is_err() {
+                                return Ok(());
+                            }
+                        }
+                        _ = stopped(&mut stop) => return Ok(()),
+                    }
+                }
+            }
+            if let Mode::PSync = mode {
+                self.config.repl_offset += size;
+                self.repl_offset.store(self.config.repl_offset, Ordering::SeqCst);
+            }
+        }
+    }
+}
+
+enum Mode {
+    PSync,
+    Sync,
+    Wait,
+}
+
+/// 等待中止信号，`EventStream`被丢弃时同样视为中止
+async fn stopped(stop: &mut watch::Receiver<bool>) {
+    let _ = stop.wait_for(|stop| *stop).await;
+}
+
+async fn send(writer: &Writer, timeout: Option<Duration>, command: &[u8], args: &[&[u8]]) -> Result<()> {
+    let buf = encode(command, args);
+    let mut writer = writer.lock().await;
+    with_timeout(timeout, async {
+        writer.write_all(&buf).await?;
+        writer.flush().await
+    })
+    .await
+}
+
+async fn with_timeout<T, F: Future<Output = Result<T>>>(timeout: Option<Duration>, future: F) -> Result<T> {
+    match timeout {
+        None => future.await,
+        Some(duration) => match tokio::time::timeout(duration, future).await {
+            Ok(result) => result,
+            Err(_) => Err(Error::new(ErrorKind::TimedOut, "operation timed out")),
+        },
+    }
+}
+
+/// 统计读取的字节数，用于计算replication offset
+struct CountReader<R> {
+    inner: R,
+    len: i64,
+}
+
+impl<R: AsyncRead + Unpin> CountReader<R> {
+    fn new(inner: R) -> CountReader<R> {
+        CountReader { inner, len: 0 }
+    }
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for CountReader<R> {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<Result<()>> {
+        let filled = buf.filled().len();
+        let poll = Pin::new(&mut self.inner).poll_read(cx, buf);
+        if let Poll::Ready(Ok(())) = poll {
+            self.len += (buf.filled().len() - filled) as i64;
+        }
+        poll
+    }
+}
+
+/// 在阻塞线程中以同步的方式读取异步连接，收到中止信号时读取以错误返回
+struct BlockingReader {
+    inner: Reader,
+    handle: Handle,
+    stop: watch::Receiver<bool>,
+    running: Arc<AtomicBool>,
+}
+
+impl Read for BlockingReader {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let BlockingReader {
+            inner,
+            handle,
+            stop,
+            running,
+        } = self;
+        handle.block_on(async {
+            tokio::select! {
+                result = inner.read(buf) => result,
+                _ = stopped(stop) => {
+                    running.store(false, Ordering::SeqCst);
+                    Err(Error::new(ErrorKind::ConnectionAborted, "Listener stopped"))
+                }
+            }
+        })
+    }
+}
+
+/// 将RDB事件转换为`OwnedEvent`，通过channel发送，channel已满时阻塞解析线程
+struct ChannelHandler {
+    events: mpsc::Sender<Result<OwnedEvent>>,
+    running: Arc<AtomicBool>,
+}
+
+impl EventHandler for ChannelHandler {
+    fn handle(&mut self, event: Event) {
+        if let Event::RDB(obj) = event {
+            if let Some(obj) = OwnedObject::from_object(&obj) {
+                if self.events.blocking_send(Ok(OwnedEvent::RDB(obj))).is_err() {
+                    self.running.store(false, Ordering::SeqCst);
+                }
+            }
+        }
+    }
+}
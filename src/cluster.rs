@@ -0,0 +1,730 @@
+/*!
+监听Redis Cluster中所有master的事件
+
+[`ClusterListener`]通过`CLUSTER NODES`获取集群的拓扑，为每个master启动一个[`Listener`]，
+每个事件都会附带产生此事件的节点ID及key所属的slot。拓扑发生变化(failover、新增或移除master)时，
+将停止已不再是master的节点的监听，并为新的master启动监听。failover之后，新的master将沿用原master的replication id及offset发起PSYNC。
+
+此外，[`slot_of`]及[`event_keys`]可用于计算事件中key所属的slot，[`SlotRouter`]则按slot对事件进行分发，便于将事件路由到下游的分片。
+
+[`ClusterListener`]: struct.ClusterListener.html
+[`Listener`]: ../listener/struct.Listener.html
+[`slot_of`]: fn.slot_of.html
+[`event_keys`]: fn.event_keys.html
+[`SlotRouter`]: struct.SlotRouter.html
+*/
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::io::{Error, ErrorKind, Result};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, sleep, JoinHandle};
+use std::time::{Duration, Instant};
+
+use log::{info, warn};
+use scheduled_thread_pool::ScheduledThreadPool;
+
+use crate::cmd::keys::{DEL, UNLINK};
+use crate::cmd::strings::{KeyValue, MSET};
+use crate::cmd::Command;
+use crate::config::Config;
+use crate::io::{self, send};
+use crate::listener::{self, Builder, ShutdownHandle};
+use crate::rdb::Object;
+use crate::resp::{Resp, RespDecode};
+use crate::{to_string, Event, EventHandler, RedisListener, Stopped};
+
+/// Redis Cluster中slot的总数
+pub const SLOTS: u16 = 16384;
+
+/// 查询集群拓扑时的连接及读写超时
+const QUERY_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// 连接master出错之后，再次尝试的间隔
+const RETRY_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Cluster相关的配置
+#[derive(Debug, Clone)]
+pub struct ClusterConfig {
+    /// 用于获取集群拓扑的节点地址，将按顺序逐个尝试
+    pub seeds: Vec<(String, u16)>,
+    /// 刷新集群拓扑的间隔
+    pub refresh_interval: Duration,
+}
+
+/// 集群中的节点信息，来自`CLUSTER NODES`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClusterNode {
+    /// 节点ID
+    pub id: String,
+    /// 节点的地址
+    pub host: String,
+    /// 节点的端口
+    pub port: u16,
+    /// 为replica时，所属master的节点ID；为master时为None
+    pub master_id: Option<String>,
+    /// 节点负责的slot范围，闭区间
+    pub slots: Vec<(u16, u16)>,
+}
+
+impl ClusterNode {
+    /// 是否为可用的master
+    pub fn is_master(&self) -> bool {
+        self.master_id.is_none()
+    }
+}
+
+/// Cluster事件处理器的定义
+///
+/// 所有master的事件都在各自的线程中产生，然后交由同一个处理器处理
+pub trait ClusterEventHandler: Send {
+    /// 处理事件
+    ///
+    /// 方法参数:
+    ///
+    /// * `node_id`: 产生此事件的master的节点ID
+    /// * `slot`: 事件中key所属的slot，事件不含key时(如`MULTI`，`FLUSHALL`，RDB的开始与结束)
+    ///   或无法确定key时(未解析的命令)为None
+    /// * `event`: Redis事件
+    fn handle(&mut self, node_id: &str, slot: Option<u16>, event: Event);
+}
+
+/// 监听Redis Cluster中所有master的事件
+///
+/// `Config`提供认证、超时、TLS等连接参数，其中的host及port将被集群中各个master的地址所替换，
+/// repl_id及repl_offset将被忽略，各个master的同步进度由`ClusterListener`自行维护。
+///
+/// 启用TLS时，查询拓扑及监听master都使用TLS连接，节点的地址取自`CLUSTER NODES`，因此集群需配置`tls-cluster yes`，
+/// 使其中的端口为TLS端口。各节点的证书均以`tls_server_name`校验，未设置时以节点的地址校验
+pub struct ClusterListener {
+    cluster: ClusterConfig,
+    config: Config,
+    event_handler: Arc<Mutex<dyn ClusterEventHandler>>,
+    running: Arc<AtomicBool>,
+    thread_pool: Arc<ScheduledThreadPool>,
+    nodes: Vec<ClusterNode>,
+    replica_of: HashMap<String, String>,
+    states: HashMap<String, Stopped>,
+    workers: HashMap<String, Worker>,
+    finished: HashSet<String>,
+    retry_at: HashMap<String, Instant>,
+}
+
+impl ClusterListener {
+    /// 创建`ClusterListener`
+    ///
+    /// 将控制变量设置为false之后，`start`将中止所有master的监听并返回
+    pub fn new(
+        cluster: ClusterConfig, config: Config, event_handler: Arc<Mutex<dyn ClusterEventHandler>>,
+        control_flag: Arc<AtomicBool>,
+    ) -> ClusterListener {
+        if cluster.seeds.is_empty() {
+            panic!("At least one seed node is required");
+        }
+        ClusterListener {
+            cluster,
+            config,
+            event_handler,
+            running: control_flag,
+            thread_pool: Arc::new(
+                ScheduledThreadPool::builder()
+                    .num_threads(1)
+                    .thread_name_pattern("cluster-heartbeat-{}")
+                    .build(),
+            ),
+            nodes: Vec::new(),
+            replica_of: HashMap::new(),
+            states: HashMap::new(),
+            workers: HashMap::new(),
+            finished: HashSet::new(),
+            retry_at: HashMap::new(),
+        }
+    }
+
+    /// 开启事件监听，直到控制变量被设置为false
+    ///
+    /// 若`Config`中`is_aof`为false，所有master的RDB处理完毕之后即返回。
+    /// 返回值为各个master(以节点ID为key)中止时的replication id及offset
+    pub fn start(&mut self) -> Result<HashMap<String, Stopped>> {
+        let nodes = self.fetch_topology()?;
+        self.update_topology(nodes);
+        let mut last_refresh = Instant::now();
+        loop {
+            self.reap(false);
+            if !self.is_running() {
+                self.reap(true);
+                return Ok(self.states.clone());
+            }
+            if !self.config.is_aof && self.workers.is_empty() && self.masters().all(|n| self.finished.contains(&n.id)) {
+                return Ok(self.states.clone());
+            }
+            if last_refresh.elapsed() >= self.cluster.refresh_interval {
+                match self.fetch_topology() {
+                    Ok(nodes) => self.update_topology(nodes),
+                    Err(err) => warn!("刷新集群拓扑失败: {}", err),
+                }
+                last_refresh = Instant::now();
+            }
+            self.rebalance();
+            sleep(Duration::from_millis(100));
+        }
+    }
+
+    /// 当前集群中的节点
+    pub fn nodes(&self) -> &[ClusterNode] {
+        &self.nodes
+    }
+
+    fn is_running(&self) -> bool {
+        self.running.load(Ordering::Relaxed)
+    }
+
+    fn masters(&self) -> impl Iterator<Item = &ClusterNode> {
+        self.nodes.iter().filter(|node| node.is_master())
+    }
+
+    /// 依次询问种子节点及已知的master，获取集群的拓扑
+    fn fetch_topology(&self) -> Result<Vec<ClusterNode>> {
+        let mut addrs = self.cluster.seeds.clone();
+        addrs.extend(self.masters().map(|node| (node.host.clone(), node.port)));
+        let mut last_err = Error::new(ErrorKind::NotFound, "No cluster node available");
+        for (host, port) in addrs {
+            match self.cluster_nodes(&host, port) {
+                Ok(nodes) => return Ok(nodes),
+                Err(err) => {
+                    warn!("节点 {}:{} 获取集群拓扑失败: {}", host, port, err);
+                    last_err = err;
+                }
+            }
+        }
+        Err(last_err)
+    }
+
+    fn cluster_nodes(&self, host: &str, port: u16) -> Result<Vec<ClusterNode>> {
+        // 与监听master时一样，按`Config`决定是否使用TLS
+        let mut config = self.config.clone();
+        config.host = host.to_owned();
+        config.port = port;
+        config.connect_timeout = Some(QUERY_TIMEOUT);
+        config.read_timeout = Some(QUERY_TIMEOUT);
+        config.write_timeout = Some(QUERY_TIMEOUT);
+        let mut conn = listener::open_transport(&config)?;
+        io::auth(&mut conn, &config.username, &config.password)?;
+        send(&mut conn, b"CLUSTER", &[b"NODES"])?;
+        match conn.decode_resp()? {
+            Resp::BulkBytes(nodes) => Ok(parse_nodes(&to_string(nodes), host)),
+            Resp::Error(err) => Err(Error::new(ErrorKind::InvalidData, err)),
+            _ => Err(Error::new(ErrorKind::InvalidData, "Unexpected CLUSTER NODES response")),
+        }
+    }
+
+    fn update_topology(&mut self, nodes: Vec<ClusterNode>) {
+        for node in &nodes {
+            if let Some(master_id) = &node.master_id {
+                self.replica_of.insert(node.id.clone(), master_id.clone());
+            }
+        }
+        if nodes != self.nodes {
+            info!("集群拓扑: {:?}", &nodes);
+            self.nodes = nodes;
+        }
+    }
+
+    /// 停止已不再是master的节点的监听，为新的master启动监听
+    fn rebalance(&mut self) {
+        let masters: HashMap<String, (String, u16)> = self
+            .masters()
+            .map(|node| (node.id.clone(), (node.host.clone(), node.port)))
+            .collect();
+        let outdated: Vec<String> = self
+            .workers
+            .iter()
+            .filter(|(id, worker)| masters.get(*id) != Some(&worker.addr))
+            .map(|(id, _)| id.clone())
+            .collect();
+        for id in outdated {
+            info!("节点 {} 已不再是master, 停止监听", &id);
+            let worker = self.workers.remove(&id).unwrap();
+            worker.shutdown();
+            self.join(id, worker);
+        }
+        if !self.is_running() {
+            return;
+        }
+        let now = Instant::now();
+        for (id, addr) in masters {
+            if self.workers.contains_key(&id) || self.finished.contains(&id) {
+                continue;
+            }
+            if let Some(retry_at) = self.retry_at.get(&id) {
+                if now < *retry_at {
+                    continue;
+                }
+            }
+            let worker = self.spawn(&id, addr);
+            self.workers.insert(id, worker);
+        }
+    }
+
+    fn spawn(&self, node_id: &str, addr: (String, u16)) -> Worker {
+        // failover之后，新的master沿用原master的进度，psync2下可继续增量同步
+        let state = self.states.get(node_id).or_else(|| {
+            self.replica_of
+                .get(node_id)
+                .and_then(|master_id| self.states.get(master_id))
+        });
+        let mut config = self.config.clone();
+        config.host = addr.0.clone();
+        config.port = addr.1;
+        match state {
+            Some(state) => {
+                config.repl_id = state.repl_id.clone();
+                config.repl_offset = state.repl_offset;
+            }
+            None => {
+                config.repl_id = String::from("?");
+                config.repl_offset = -1;
+            }
+        }
+        info!("开始监听master {} {}:{}", node_id, &addr.0, addr.1);
+
+        let running = Arc::new(AtomicBool::new(true));
+        let shutdown_handle = Arc::new(Mutex::new(None));
+        let handler = NodeEventHandler {
+            node_id: node_id.to_owned(),
+            handler: Arc::clone(&self.event_handler),
+        };
+        let thread_pool = Arc::clone(&self.thread_pool);
+        let link_running = Arc::clone(&running);
+        let link_shutdown_handle = Arc::clone(&shutdown_handle);
+        let thread = thread::Builder::new()
+            .name(format!("cluster-{}", node_id))
+            .spawn(move || {
+                let mut builder = Builder::new();
+                builder.with_config(config);
+                builder.with_control_flag(link_running);
+                builder.with_thread_pool(thread_pool);
+                builder.with_event_handler(Rc::new(RefCell::new(handler)));
+                let mut listener = builder.build();
+                *link_shutdown_handle.lock().unwrap() = Some(listener.shutdown_handle());
+                let result = listener.start().map(|_| ());
+                let stopped = Stopped {
+                    repl_id: listener.config.repl_id.clone(),
+                    repl_offset: listener.config.repl_offset,
+                };
+                (stopped, result)
+            })
+            .expect("spawn cluster listener failed");
+        Worker {
+            addr,
+            running,
+            shutdown_handle,
+            thread,
+        }
+    }
+
+    /// 回收已结束的监听线程，`all`为true时中止并回收所有线程
+    fn reap(&mut self, all: bool) {
+        let ids: Vec<String> = self
+            .workers
+            .iter()
+            .filter(|(_, worker)| all || worker.thread.is_finished())
+            .map(|(id, _)| id.clone())
+            .collect();
+        for id in ids {
+            let worker = self.workers.remove(&id).unwrap();
+            if all {
+                worker.shutdown();
+            }
+            self.join(id, worker);
+        }
+    }
+
+    fn join(&mut self, id: String, worker: Worker) {
+        let stopped_by_us = !worker.running.load(Ordering::SeqCst);
+        match worker.thread.join() {
+            Ok((stopped, result)) => {
+                match result {
+                    Ok(()) if !stopped_by_us => {
+                        info!("master {} 处理完毕", &id);
+                        self.finished.insert(id.clone());
+                    }
+                    Ok(()) => {}
+                    Err(err) => {
+                        warn!("master {} 的连接中断: {}", &id, err);
+                        self.retry_at.insert(id.clone(), Instant::now() + RETRY_INTERVAL);
+                    }
+                }
+                self.states.insert(id, stopped);
+            }
+            Err(_) => {
+                warn!("master {} 的监听线程异常退出", &id);
+                self.retry_at.insert(id, Instant::now() + RETRY_INTERVAL);
+            }
+        }
+    }
+}
+
+impl Drop for ClusterListener {
+    fn drop(&mut self) {
+        for (_, worker) in self.workers.drain() {
+            worker.shutdown();
+            let _ = worker.thread.join();
+        }
+    }
+}
+
+/// 单个master的监听线程
+struct Worker {
+    addr: (String, u16),
+    running: Arc<AtomicBool>,
+    shutdown_handle: Arc<Mutex<Option<ShutdownHandle>>>,
+    thread: JoinHandle<(Stopped, Result<()>)>,
+}
+
+impl Worker {
+    fn shutdown(&self) {
+        self.running.store(false, Ordering::SeqCst);
+        // 线程尚未创建Listener时，Listener会在建立连接时发现控制变量已为false
+        if let Some(handle) = self.shutdown_handle.lock().unwrap().as_ref() {
+            handle.shutdown();
+        }
+    }
+}
+
+/// 为事件附加节点ID及slot，再交由`ClusterEventHandler`处理
+struct NodeEventHandler {
+    node_id: String,
+    handler: Arc<Mutex<dyn ClusterEventHandler>>,
+}
+
+impl EventHandler for NodeEventHandler {
+    fn handle(&mut self, event: Event) {
+        // 包含多个key的命令，取第一个key(在集群中这些key必定属于同一个slot)
+        let slot = event_keys(&event).and_then(|keys| keys.first().map(|key| slot_of(key)));
+        self.handler.lock().unwrap().handle(&self.node_id, slot, event);
+    }
+}
+
+/// 按slot处理事件的处理器，配合[`SlotRouter`]使用
+///
+/// [`SlotRouter`]: struct.SlotRouter.html
+pub trait SlotEventHandler {
+    /// 处理事件
+    ///
+    /// 方法参数:
+    ///
+    /// * `slot`: 事件中key所属的slot，事件不含key时为None
+    /// * `event`: Redis事件
+    fn handle(&mut self, slot: Option<u16>, event: Event);
+
+    /// 处理涉及多个slot且无法拆分的命令，如key分别属于不同slot的`RENAME`，在Redis Cluster中此类命令会被拒绝
+    ///
+    /// 无法确定key的未解析命令([`RawCommand`])也由此方法处理，此时`slots`为空
+    ///
+    /// [`RawCommand`]: ../cmd/struct.RawCommand.html
+    ///
+    /// 默认输出警告并丢弃此命令
+    fn handle_cross_slot(&mut self, slots: &[u16], event: Event) {
+        if let Event::AOF(cmd) = event {
+            warn!("跨slot的命令被丢弃, slots: {:?}, command: {:?}", slots, cmd);
+        }
+    }
+}
+
+/// 按key所属的slot分发事件
+///
+/// `DEL`、`UNLINK`及`MSET`中的key属于不同的slot时，将按slot拆分为多条命令分别处理，
+/// 其它涉及多个slot的命令以及无法确定key的未解析命令交由[`SlotEventHandler::handle_cross_slot`]处理
+///
+/// [`SlotEventHandler::handle_cross_slot`]: trait.SlotEventHandler.html#method.handle_cross_slot
+pub struct SlotRouter<H: SlotEventHandler> {
+    handler: H,
+}
+
+impl<H: SlotEventHandler> SlotRouter<H> {
+    pub fn new(handler: H) -> SlotRouter<H> {
+        SlotRouter { handler }
+    }
+
+    pub fn handler(&self) -> &H {
+        &self.handler
+    }
+
+    pub fn handler_mut(&mut self) -> &mut H {
+        &mut self.handler
+    }
+}
+
+impl<H: SlotEventHandler> EventHandler for SlotRouter<H> {
+    fn handle(&mut self, event: Event) {
+        // 按slot对key进行分组，记录key的下标，保持key的出现顺序
+        let keys = match event_keys(&event) {
+            Some(keys) => keys,
+            None => return self.handler.handle_cross_slot(&[], event),
+        };
+        let mut groups: Vec<(u16, Vec<usize>)> = Vec::new();
+        for (i, key) in keys.iter().enumerate() {
+            let slot = slot_of(key);
+            match groups.iter_mut().find(|(s, _)| *s == slot) {
+                Some((_, indexes)) => indexes.push(i),
+                None => groups.push((slot, vec![i])),
+            }
+        }
+        match groups.len() {
+            0 => self.handler.handle(None, event),
+            1 => self.handler.handle(Some(groups[0].0), event),
+            _ => match event {
+                Event::AOF(Command::DEL(del)) => {
+                    for (slot, indexes) in groups {
+                        let cmd = DEL {
+                            keys: indexes.iter().map(|i| del.keys[*i]).collect(),
+                        };
+                        self.handler.handle(Some(slot), Event::AOF(Command::DEL(&cmd)));
+                    }
+                }
+                Event::AOF(Command::UNLINK(unlink)) => {
+                    for (slot, indexes) in groups {
+                        let cmd = UNLINK {
+                            keys: indexes.iter().map(|i| unlink.keys[*i]).collect(),
+                        };
+                        self.handler.handle(Some(slot), Event::AOF(Command::UNLINK(&cmd)));
+                    }
+                }
+                Event::AOF(Command::MSET(mset)) => {
+                    for (slot, indexes) in groups {
+                        let cmd = MSET {
+                            key_values: indexes
+                                .iter()
+                                .map(|i| KeyValue {
+                                    key: mset.key_values[*i].key,
+                                    value: mset.key_values[*i].value,
+                                })
+                                .collect(),
+                        };
+                        self.handler.handle(Some(slot), Event::AOF(Command::MSET(&cmd)));
+                    }
+                }
+                event => {
+                    let slots: Vec<u16> = groups.iter().map(|(slot, _)| *slot).collect();
+                    self.handler.handle_cross_slot(&slots, event);
+                }
+            },
+        }
+    }
+}
+
+/// 解析`CLUSTER NODES`的结果，`host`为被查询节点的地址，节点未获知自身地址时以此代替
+pub(crate) fn parse_nodes(text: &str, host: &str) -> Vec<ClusterNode> {
+    let mut nodes = Vec::new();
+    for line in text.lines() {
+        // <id> <ip:port@cport[,hostname]> <flags> <master> <ping-sent> <pong-recv> <config-epoch> <link-state> <slot> ...
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 8 {
+            continue;
+        }
+        let flags: Vec<&str> = fields[2].split(',').collect();
+        if flags
+            .iter()
+            .any(|flag| *flag == "fail" || *flag == "handshake" || *flag == "noaddr")
+        {
+            continue;
+        }
+        let addr = fields[1].split(',').next().unwrap();
+        let addr = addr.split('@').next().unwrap();
+        let (node_host, port) = match addr.rfind(':') {
+            Some(i) => (&addr[..i], &addr[i + 1..]),
+            None => continue,
+        };
+        let port = match port.parse::<u16>() {
+            Ok(port) if port != 0 => port,
+            _ => continue,
+        };
+        let node_host = if node_host.is_empty() { host } else { node_host };
+        let master_id = if flags.contains(&"master") {
+            None
+        } else if fields[3] != "-" {
+            Some(fields[3].to_owned())
+        } else {
+            continue;
+        };
+        let mut slots = Vec::new();
+        for slot in &fields[8..] {
+            // 迁移中的slot形如[slot->-node]，不计入
+            if slot.starts_with('[') {
+                continue;
+            }
+            let range = match slot.find('-') {
+                Some(i) => (slot[..i].parse::<u16>(), slot[i + 1..].parse::<u16>()),
+                None => (slot.parse::<u16>(), slot.parse::<u16>()),
+            };
+            if let (Ok(start), Ok(end)) = range {
+                slots.push((start, end));
+            }
+        }
+        nodes.push(ClusterNode {
+            id: fields[0].to_owned(),
+            host: node_host.to_owned(),
+            port,
+            master_id,
+            slots,
+        });
+    }
+    nodes.sort_by(|a, b| a.id.cmp(&b.id));
+    nodes
+}
+
+/// 计算key所属的slot，支持hash tag，即key中包含`{...}`时，仅以花括号中的内容计算
+pub fn slot_of(key: &[u8]) -> u16 {
+    let key = match key.iter().position(|b| *b == b'{') {
+        Some(start) => match key[start + 1..].iter().position(|b| *b == b'}') {
+            Some(len) if len > 0 => &key[start + 1..start + 1 + len],
+            _ => key,
+        },
+        None => key,
+    };
+    crc16(key) % SLOTS
+}
+
+/// CRC16-CCITT(XMODEM)，Redis Cluster使用此算法计算slot
+fn crc16(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0;
+    for b in data {
+        crc ^= (*b as u16) << 8;
+        for _ in 0..8 {
+            if crc & 0x8000 != 0 {
+                crc = (crc << 1) ^ 0x1021;
+            } else {
+                crc <<= 1;
+            }
+        }
+    }
+    crc
+}
+
+/// 获取事件中所有的key，依照其在命令中出现的顺序
+///
+/// 适用于RDB中的各类数据及已解析的命令。未解析的命令([`RawCommand`])无法确定哪些参数是key，返回None，
+/// 而不含key的事件(如`MULTI`，RDB的开始与结束)返回空的`Vec`
+///
+/// [`RawCommand`]: ../cmd/struct.RawCommand.html
+pub fn event_keys<'a>(event: &'a Event) -> Option<Vec<&'a [u8]>> {
+    match event {
+        Event::RDB(obj) => Some(match obj {
+            Object::String(kv) => vec![kv.key],
+            Object::List(list) => vec![list.key],
+            Object::Set(set) => vec![set.key],
+            Object::SortedSet(set) => vec![set.key],
+            Object::Hash(hash) => vec![hash.key],
+            Object::Module(key, _, _) => vec![key],
+            Object::Stream(key, _) => vec![key],
+            Object::BOR | Object::EOR => Vec::new(),
+        }),
+        Event::AOF(cmd) => command_keys(cmd),
+    }
+}
+
+fn command_keys<'a>(cmd: &'a Command) -> Option<Vec<&'a [u8]>> {
+    let keys = match cmd {
+        Command::APPEND(cmd) => vec![cmd.key],
+        Command::BITFIELD(cmd) => vec![cmd.key],
+        Command::BITOP(cmd) => with_keys(cmd.dest_key, cmd.keys.iter().copied()),
+        Command::BRPOPLPUSH(cmd) => vec![cmd.source, cmd.destination],
+        Command::DECR(cmd) => vec![cmd.key],
+        Command::DECRBY(cmd) => vec![cmd.key],
+        Command::DEL(cmd) => cmd.keys.clone(),
+        Command::EVAL(cmd) => cmd.keys.clone(),
+        Command::EVALSHA(cmd) => cmd.keys.clone(),
+        Command::EXPIRE(cmd) => vec![cmd.key],
+        Command::EXPIREAT(cmd) => vec![cmd.key],
+        Command::GETSET(cmd) => vec![cmd.key],
+        Command::HDEL(cmd) => vec![cmd.key],
+        Command::HINCRBY(cmd) => vec![cmd.key],
+        Command::HMSET(cmd) => vec![cmd.key],
+        Command::HSET(cmd) => vec![cmd.key],
+        Command::HSETNX(cmd) => vec![cmd.key],
+        Command::INCR(cmd) => vec![cmd.key],
+        Command::INCRBY(cmd) => vec![cmd.key],
+        Command::LINSERT(cmd) => vec![cmd.key],
+        Command::LPOP(cmd) => vec![cmd.key],
+        Command::LPUSH(cmd) => vec![cmd.key],
+        Command::LPUSHX(cmd) => vec![cmd.key],
+        Command::LREM(cmd) => vec![cmd.key],
+        Command::LSET(cmd) => vec![cmd.key],
+        Command::LTRIM(cmd) => vec![cmd.key],
+        Command::MOVE(cmd) => vec![cmd.key],
+        Command::MSET(cmd) => cmd.key_values.iter().map(|kv| kv.key).collect(),
+        Command::MSETNX(cmd) => cmd.key_values.iter().map(|kv| kv.key).collect(),
+        Command::PERSIST(cmd) => vec![cmd.key],
+        Command::PEXPIRE(cmd) => vec![cmd.key],
+        Command::PEXPIREAT(cmd) => vec![cmd.key],
+        Command::PFADD(cmd) => vec![cmd.key],
+        Command::PFCOUNT(cmd) => cmd.keys.clone(),
+        Command::PFMERGE(cmd) => with_keys(cmd.dest_key, cmd.source_keys.iter().copied()),
+        Command::PSETEX(cmd) => vec![cmd.key],
+        Command::RENAME(cmd) => vec![cmd.key, cmd.new_key],
+        Command::RENAMENX(cmd) => vec![cmd.key, cmd.new_key],
+        Command::RESTORE(cmd) => vec![cmd.key],
+        Command::RPOP(cmd) => vec![cmd.key],
+        Command::RPOPLPUSH(cmd) => vec![cmd.source, cmd.destination],
+        Command::RPUSH(cmd) => vec![cmd.key],
+        Command::RPUSHX(cmd) => vec![cmd.key],
+        Command::SADD(cmd) => vec![cmd.key],
+        Command::SDIFFSTORE(cmd) => with_keys(cmd.destination, cmd.keys.iter().copied()),
+        Command::SET(cmd) => vec![cmd.key],
+        Command::SETBIT(cmd) => vec![cmd.key],
+        Command::SETEX(cmd) => vec![cmd.key],
+        Command::SETNX(cmd) => vec![cmd.key],
+        Command::SETRANGE(cmd) => vec![cmd.key],
+        Command::SINTERSTORE(cmd) => with_keys(cmd.destination, cmd.keys.iter().copied()),
+        Command::SMOVE(cmd) => vec![cmd.source, cmd.destination],
+        Command::SORT(cmd) => with_keys(cmd.key, cmd.destination),
+        Command::SREM(cmd) => vec![cmd.key],
+        Command::SUNIONSTORE(cmd) => with_keys(cmd.destination, cmd.keys.iter().copied()),
+        Command::UNLINK(cmd) => cmd.keys.clone(),
+        Command::ZADD(cmd) => vec![cmd.key],
+        Command::ZINCRBY(cmd) => vec![cmd.key],
+        Command::ZINTERSTORE(cmd) => with_keys(cmd.destination, cmd.keys.iter().copied()),
+        Command::ZPOPMAX(cmd) => vec![cmd.key],
+        Command::ZPOPMIN(cmd) => vec![cmd.key],
+        Command::ZREM(cmd) => vec![cmd.key],
+        Command::ZREMRANGEBYLEX(cmd) => vec![cmd.key],
+        Command::ZREMRANGEBYRANK(cmd) => vec![cmd.key],
+        Command::ZREMRANGEBYSCORE(cmd) => vec![cmd.key],
+        Command::ZUNIONSTORE(cmd) => with_keys(cmd.destination, cmd.keys.iter().copied()),
+        Command::XACK(cmd) => vec![cmd.key],
+        Command::XADD(cmd) => vec![cmd.key],
+        Command::XCLAIM(cmd) => vec![cmd.key],
+        Command::XDEL(cmd) => vec![cmd.key],
+        Command::XGROUP(cmd) => {
+            if let Some(create) = &cmd.create {
+                vec![create.key]
+            } else if let Some(set_id) = &cmd.set_id {
+                vec![set_id.key]
+            } else if let Some(destroy) = &cmd.destroy {
+                vec![destroy.key]
+            } else {
+                cmd.del_consumer.iter().map(|del_consumer| del_consumer.key).collect()
+            }
+        }
+        Command::XTRIM(cmd) => vec![cmd.key],
+        Command::EXEC
+        | Command::MULTI
+        | Command::FLUSHALL(_)
+        | Command::FLUSHDB(_)
+        | Command::PUBLISH(_)
+        | Command::SCRIPTFLUSH
+        | Command::SCRIPTLOAD(_)
+        | Command::SELECT(_)
+        | Command::SWAPDB(_) => Vec::new(),
+        Command::Other(_) => return None,
+    };
+    Some(keys)
+}
+
+fn with_keys<'a, I: IntoIterator<Item = &'a [u8]>>(first: &'a [u8], rest: I) -> Vec<&'a [u8]> {
+    let mut keys = vec![first];
+    keys.extend(rest);
+    keys
+}
@@ -6,14 +6,14 @@ Connection相关的命令定义、解析
 [Redis Command Reference]: https://redis.io/commands#connection
 */
 
-use std::slice::Iter;
+use crate::cmd::Args;
 
 #[derive(Debug)]
 pub struct SELECT {
     pub db: i32,
 }
 
-pub(crate) fn parse_select(mut iter: Iter<Vec<u8>>) -> SELECT {
+pub(crate) fn parse_select(mut iter: Args) -> SELECT {
     let db = String::from_utf8_lossy(iter.next().unwrap());
     let db = db.parse::<i32>().unwrap();
     SELECT { db }
@@ -25,7 +25,7 @@ pub struct SWAPDB<'a> {
     pub index2: &'a [u8],
 }
 
-pub(crate) fn parse_swapdb(mut iter: Iter<Vec<u8>>) -> SWAPDB {
+pub(crate) fn parse_swapdb(mut iter: Args) -> SWAPDB {
     let index1 = iter.next().unwrap();
     let index2 = iter.next().unwrap();
     SWAPDB { index1, index2 }
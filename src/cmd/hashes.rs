@@ -6,7 +6,7 @@ Hashes相关的命令定义、解析
 [Redis Command Reference]: https://redis.io/commands#hash
 */
 
-use std::slice::Iter;
+use crate::cmd::Args;
 
 #[derive(Debug)]
 pub struct HDEL<'a> {
@@ -14,11 +14,11 @@ pub struct HDEL<'a> {
     pub fields: Vec<&'a [u8]>,
 }
 
-pub(crate) fn parse_hdel(mut iter: Iter<Vec<u8>>) -> HDEL {
+pub(crate) fn parse_hdel(mut iter: Args) -> HDEL {
     let key = iter.next().unwrap();
     let mut fields = Vec::new();
     while let Some(field) = iter.next() {
-        fields.push(field.as_slice());
+        fields.push(field);
     }
     HDEL { key, fields }
 }
@@ -30,7 +30,7 @@ pub struct HINCRBY<'a> {
     pub increment: &'a [u8],
 }
 
-pub(crate) fn parse_hincrby(mut iter: Iter<Vec<u8>>) -> HINCRBY {
+pub(crate) fn parse_hincrby(mut iter: Args) -> HINCRBY {
     let key = iter.next().unwrap();
     let field = iter.next().unwrap();
     let increment = iter.next().unwrap();
@@ -55,7 +55,7 @@ pub struct Field<'a> {
     pub value: &'a [u8],
 }
 
-pub(crate) fn parse_hmset(mut iter: Iter<Vec<u8>>) -> HMSET {
+pub(crate) fn parse_hmset(mut iter: Args) -> HMSET {
     let key = iter.next().unwrap();
     let mut fields = Vec::new();
     loop {
@@ -73,7 +73,7 @@ pub(crate) fn parse_hmset(mut iter: Iter<Vec<u8>>) -> HMSET {
     HMSET { key, fields }
 }
 
-pub(crate) fn parse_hset(mut iter: Iter<Vec<u8>>) -> HSET {
+pub(crate) fn parse_hset(mut iter: Args) -> HSET {
     let key = iter.next().unwrap();
     let mut fields = Vec::new();
     loop {
@@ -98,7 +98,7 @@ pub struct HSETNX<'a> {
     pub value: &'a [u8],
 }
 
-pub(crate) fn parse_hsetnx(mut iter: Iter<Vec<u8>>) -> HSETNX {
+pub(crate) fn parse_hsetnx(mut iter: Args) -> HSETNX {
     let key = iter.next().unwrap();
     let field = iter.next().unwrap();
     let value = iter.next().unwrap();
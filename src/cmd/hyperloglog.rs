@@ -6,7 +6,7 @@ HyperLogLog相关的命令定义、解析
 [Redis Command Reference]: https://redis.io/commands#hyperloglog
 */
 
-use std::slice::Iter;
+use crate::cmd::Args;
 
 #[derive(Debug)]
 pub struct PFADD<'a> {
@@ -14,11 +14,11 @@ pub struct PFADD<'a> {
     pub elements: Vec<&'a [u8]>,
 }
 
-pub(crate) fn parse_pfadd(mut iter: Iter<Vec<u8>>) -> PFADD {
+pub(crate) fn parse_pfadd(mut iter: Args) -> PFADD {
     let key = iter.next().unwrap();
     let mut elements = Vec::new();
     while let Some(element) = iter.next() {
-        elements.push(element.as_slice());
+        elements.push(element);
     }
     PFADD { key, elements }
 }
@@ -28,10 +28,10 @@ pub struct PFCOUNT<'a> {
     pub keys: Vec<&'a [u8]>,
 }
 
-pub(crate) fn parse_pfcount(mut iter: Iter<Vec<u8>>) -> PFCOUNT {
+pub(crate) fn parse_pfcount(mut iter: Args) -> PFCOUNT {
     let mut keys = Vec::new();
     while let Some(key) = iter.next() {
-        keys.push(key.as_slice());
+        keys.push(key);
     }
     PFCOUNT { keys }
 }
@@ -42,11 +42,11 @@ pub struct PFMERGE<'a> {
     pub source_keys: Vec<&'a [u8]>,
 }
 
-pub(crate) fn parse_pfmerge(mut iter: Iter<Vec<u8>>) -> PFMERGE {
+pub(crate) fn parse_pfmerge(mut iter: Args) -> PFMERGE {
     let dest_key = iter.next().unwrap();
     let mut source_keys = Vec::new();
     while let Some(source) = iter.next() {
-        source_keys.push(source.as_slice());
+        source_keys.push(source);
     }
     PFMERGE { dest_key, source_keys }
 }
@@ -6,16 +6,16 @@ Keys相关的命令定义、解析
 [Redis Command Reference]: https://redis.io/commands#generic
 */
 
-use std::slice::Iter;
+use crate::cmd::Args;
 
 use crate::cmd::keys::ORDER::{ASC, DESC};
 
 #[derive(Debug)]
 pub struct DEL<'a> {
-    pub keys: Vec<&'a Vec<u8>>,
+    pub keys: Vec<&'a [u8]>,
 }
 
-pub(crate) fn parse_del(iter: Iter<Vec<u8>>) -> DEL {
+pub(crate) fn parse_del(iter: Args) -> DEL {
     let mut keys = Vec::new();
     for next_key in iter {
         keys.push(next_key);
@@ -28,7 +28,7 @@ pub struct PERSIST<'a> {
     pub key: &'a [u8],
 }
 
-pub(crate) fn parse_persist(mut iter: Iter<Vec<u8>>) -> PERSIST {
+pub(crate) fn parse_persist(mut iter: Args) -> PERSIST {
     let key = iter.next().unwrap();
     PERSIST { key }
 }
@@ -39,7 +39,7 @@ pub struct EXPIRE<'a> {
     pub seconds: &'a [u8],
 }
 
-pub(crate) fn parse_expire(mut iter: Iter<Vec<u8>>) -> EXPIRE {
+pub(crate) fn parse_expire(mut iter: Args) -> EXPIRE {
     let key = iter.next().unwrap();
     let seconds = iter.next().unwrap();
     EXPIRE { key, seconds }
@@ -51,7 +51,7 @@ pub struct PEXPIRE<'a> {
     pub milliseconds: &'a [u8],
 }
 
-pub(crate) fn parse_pexpire(mut iter: Iter<Vec<u8>>) -> PEXPIRE {
+pub(crate) fn parse_pexpire(mut iter: Args) -> PEXPIRE {
     let key = iter.next().unwrap();
     let milliseconds = iter.next().unwrap();
     PEXPIRE { key, milliseconds }
@@ -63,7 +63,7 @@ pub struct EXPIREAT<'a> {
     pub timestamp: &'a [u8],
 }
 
-pub(crate) fn parse_expireat(mut iter: Iter<Vec<u8>>) -> EXPIREAT {
+pub(crate) fn parse_expireat(mut iter: Args) -> EXPIREAT {
     let key = iter.next().unwrap();
     let timestamp = iter.next().unwrap();
     EXPIREAT { key, timestamp }
@@ -75,7 +75,7 @@ pub struct PEXPIREAT<'a> {
     pub mill_timestamp: &'a [u8],
 }
 
-pub(crate) fn parse_pexpireat(mut iter: Iter<Vec<u8>>) -> PEXPIREAT {
+pub(crate) fn parse_pexpireat(mut iter: Args) -> PEXPIREAT {
     let key = iter.next().unwrap();
     let mill_timestamp = iter.next().unwrap();
     PEXPIREAT { key, mill_timestamp }
@@ -87,7 +87,7 @@ pub struct MOVE<'a> {
     pub db: &'a [u8],
 }
 
-pub(crate) fn parse_move(mut iter: Iter<Vec<u8>>) -> MOVE {
+pub(crate) fn parse_move(mut iter: Args) -> MOVE {
     let key = iter.next().unwrap();
     let db = iter.next().unwrap();
     MOVE { key, db }
@@ -99,7 +99,7 @@ pub struct RENAME<'a> {
     pub new_key: &'a [u8],
 }
 
-pub(crate) fn parse_rename(mut iter: Iter<Vec<u8>>) -> RENAME {
+pub(crate) fn parse_rename(mut iter: Args) -> RENAME {
     let key = iter.next().unwrap();
     let new_key = iter.next().unwrap();
     RENAME { key, new_key }
@@ -111,7 +111,7 @@ pub struct RENAMENX<'a> {
     pub new_key: &'a [u8],
 }
 
-pub(crate) fn parse_renamenx(mut iter: Iter<Vec<u8>>) -> RENAMENX {
+pub(crate) fn parse_renamenx(mut iter: Args) -> RENAMENX {
     let key = iter.next().unwrap();
     let new_key = iter.next().unwrap();
     RENAMENX { key, new_key }
@@ -128,7 +128,7 @@ pub struct RESTORE<'a> {
     pub freq: Option<&'a [u8]>,
 }
 
-pub(crate) fn parse_restore(mut iter: Iter<Vec<u8>>) -> RESTORE {
+pub(crate) fn parse_restore(mut iter: Args) -> RESTORE {
     let key = iter.next().unwrap();
     let ttl = iter.next().unwrap();
     let value = iter.next().unwrap();
@@ -143,9 +143,9 @@ pub(crate) fn parse_restore(mut iter: Iter<Vec<u8>>) -> RESTORE {
         } else if &arg == "ABSTTL" {
             abs_ttl = Some(true);
         } else if &arg == "IDLETIME" {
-            idle_time = Some(iter.next().unwrap().as_slice());
+            idle_time = Some(iter.next().unwrap());
         } else if &arg == "FREQ" {
-            freq = Some(iter.next().unwrap().as_slice());
+            freq = Some(iter.next().unwrap());
         }
     }
     RESTORE {
@@ -182,7 +182,7 @@ pub enum ORDER {
     DESC,
 }
 
-pub(crate) fn parse_sort(mut iter: Iter<Vec<u8>>) -> SORT {
+pub(crate) fn parse_sort(mut iter: Args) -> SORT {
     let key = iter.next().unwrap();
     let mut order = None;
     let mut alpha = None;
@@ -206,13 +206,13 @@ pub(crate) fn parse_sort(mut iter: Iter<Vec<u8>>) -> SORT {
             limit = Some(LIMIT { offset, count });
         } else if &arg_upper == "STORE" {
             let store = iter.next().unwrap();
-            destination = Some(store.as_slice());
+            destination = Some(store);
         } else if &arg_upper == "BY" {
             let pattern = iter.next().unwrap();
-            by_pattern = Some(pattern.as_slice());
+            by_pattern = Some(pattern);
         } else if &arg_upper == "GET" {
             let next_pattern = iter.next().unwrap();
-            patterns.push(next_pattern.as_slice());
+            patterns.push(next_pattern);
         }
     }
     if !patterns.is_empty() {
@@ -234,10 +234,10 @@ pub struct UNLINK<'a> {
     pub keys: Vec<&'a [u8]>,
 }
 
-pub(crate) fn parse_unlink(mut iter: Iter<Vec<u8>>) -> UNLINK {
+pub(crate) fn parse_unlink(mut iter: Args) -> UNLINK {
     let mut keys = Vec::new();
     while let Some(next_key) = iter.next() {
-        keys.push(next_key.as_slice());
+        keys.push(next_key);
     }
     UNLINK { keys }
 }
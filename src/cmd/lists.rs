@@ -6,7 +6,7 @@ Lists相关的命令定义、解析
 [Redis Command Reference]: https://redis.io/commands#list
 */
 
-use std::slice::Iter;
+use crate::cmd::Args;
 
 use crate::cmd::lists::POSITION::{AFTER, BEFORE};
 
@@ -17,7 +17,7 @@ pub struct BRPOPLPUSH<'a> {
     pub timeout: &'a [u8],
 }
 
-pub(crate) fn parse_brpoplpush(mut iter: Iter<Vec<u8>>) -> BRPOPLPUSH {
+pub(crate) fn parse_brpoplpush(mut iter: Args) -> BRPOPLPUSH {
     let source = iter.next().unwrap();
     let destination = iter.next().unwrap();
     let timeout = iter.next().unwrap();
@@ -42,7 +42,7 @@ pub enum POSITION {
     AFTER,
 }
 
-pub(crate) fn parse_linsert(mut iter: Iter<Vec<u8>>) -> LINSERT {
+pub(crate) fn parse_linsert(mut iter: Args) -> LINSERT {
     let key = iter.next().unwrap();
     let next_arg = iter.next().unwrap();
     let position;
@@ -67,7 +67,7 @@ pub struct LPOP<'a> {
     pub key: &'a [u8],
 }
 
-pub(crate) fn parse_lpop(mut iter: Iter<Vec<u8>>) -> LPOP {
+pub(crate) fn parse_lpop(mut iter: Args) -> LPOP {
     let key = iter.next().unwrap();
     LPOP { key }
 }
@@ -78,11 +78,11 @@ pub struct LPUSH<'a> {
     pub elements: Vec<&'a [u8]>,
 }
 
-pub(crate) fn parse_lpush(mut iter: Iter<Vec<u8>>) -> LPUSH {
+pub(crate) fn parse_lpush(mut iter: Args) -> LPUSH {
     let key = iter.next().unwrap();
     let mut elements = Vec::new();
     while let Some(ele) = iter.next() {
-        elements.push(ele.as_slice());
+        elements.push(ele);
     }
     LPUSH { key, elements }
 }
@@ -93,11 +93,11 @@ pub struct LPUSHX<'a> {
     pub elements: Vec<&'a [u8]>,
 }
 
-pub(crate) fn parse_lpushx(mut iter: Iter<Vec<u8>>) -> LPUSHX {
+pub(crate) fn parse_lpushx(mut iter: Args) -> LPUSHX {
     let key = iter.next().unwrap();
     let mut elements = Vec::new();
     while let Some(ele) = iter.next() {
-        elements.push(ele.as_slice());
+        elements.push(ele);
     }
     LPUSHX { key, elements }
 }
@@ -109,7 +109,7 @@ pub struct LREM<'a> {
     pub element: &'a [u8],
 }
 
-pub(crate) fn parse_lrem(mut iter: Iter<Vec<u8>>) -> LREM {
+pub(crate) fn parse_lrem(mut iter: Args) -> LREM {
     let key = iter.next().unwrap();
     let count = iter.next().unwrap();
     let element = iter.next().unwrap();
@@ -123,7 +123,7 @@ pub struct LSET<'a> {
     pub element: &'a [u8],
 }
 
-pub(crate) fn parse_lset(mut iter: Iter<Vec<u8>>) -> LSET {
+pub(crate) fn parse_lset(mut iter: Args) -> LSET {
     let key = iter.next().unwrap();
     let index = iter.next().unwrap();
     let element = iter.next().unwrap();
@@ -137,7 +137,7 @@ pub struct LTRIM<'a> {
     pub stop: &'a [u8],
 }
 
-pub(crate) fn parse_ltrim(mut iter: Iter<Vec<u8>>) -> LTRIM {
+pub(crate) fn parse_ltrim(mut iter: Args) -> LTRIM {
     let key = iter.next().unwrap();
     let start = iter.next().unwrap();
     let stop = iter.next().unwrap();
@@ -149,7 +149,7 @@ pub struct RPOP<'a> {
     pub key: &'a [u8],
 }
 
-pub(crate) fn parse_rpop(mut iter: Iter<Vec<u8>>) -> RPOP {
+pub(crate) fn parse_rpop(mut iter: Args) -> RPOP {
     let key = iter.next().unwrap();
     RPOP { key }
 }
@@ -160,7 +160,7 @@ pub struct RPOPLPUSH<'a> {
     pub destination: &'a [u8],
 }
 
-pub(crate) fn parse_rpoplpush(mut iter: Iter<Vec<u8>>) -> RPOPLPUSH {
+pub(crate) fn parse_rpoplpush(mut iter: Args) -> RPOPLPUSH {
     let source = iter.next().unwrap();
     let destination = iter.next().unwrap();
     RPOPLPUSH { source, destination }
@@ -172,11 +172,11 @@ pub struct RPUSH<'a> {
     pub elements: Vec<&'a [u8]>,
 }
 
-pub(crate) fn parse_rpush(mut iter: Iter<Vec<u8>>) -> RPUSH {
+pub(crate) fn parse_rpush(mut iter: Args) -> RPUSH {
     let key = iter.next().unwrap();
     let mut elements = Vec::new();
     while let Some(ele) = iter.next() {
-        elements.push(ele.as_slice());
+        elements.push(ele);
     }
     RPUSH { key, elements }
 }
@@ -187,11 +187,11 @@ pub struct RPUSHX<'a> {
     pub elements: Vec<&'a [u8]>,
 }
 
-pub(crate) fn parse_rpushx(mut iter: Iter<Vec<u8>>) -> RPUSHX {
+pub(crate) fn parse_rpushx(mut iter: Args) -> RPUSHX {
     let key = iter.next().unwrap();
     let mut elements = Vec::new();
     while let Some(ele) = iter.next() {
-        elements.push(ele.as_slice());
+        elements.push(ele);
     }
     RPUSHX { key, elements }
 }
@@ -24,7 +24,9 @@ use crate::cmd::sets::*;
 use crate::cmd::sorted_sets::*;
 use crate::cmd::streams::{XACK, XADD, XCLAIM, XDEL, XGROUP, XTRIM};
 use crate::cmd::strings::*;
-use crate::{Event, EventHandler};
+use crate::resp::{CommandFrame, FrameArgs};
+use crate::{to_string, Event, EventHandler};
+use std::slice::Iter;
 
 pub mod connection;
 pub mod hashes;
@@ -128,352 +130,421 @@ pub enum Command<'a> {
     Other(RawCommand),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct RawCommand {
     pub name: String,
     pub args: Vec<Vec<u8>>,
 }
 
+impl RawCommand {
+    /// 由master传播过来的命令创建，第一个元素为命令名
+    #[cfg(feature = "async-tokio")]
+    pub(crate) fn from_args(mut data: Vec<Vec<u8>>) -> Option<RawCommand> {
+        if data.is_empty() {
+            return None;
+        }
+        let name = String::from_utf8_lossy(&data.remove(0)).to_uppercase();
+        Some(RawCommand { name, args: data })
+    }
+
+    /// 由[`CommandFrame`]创建，复制其中的参数
+    ///
+    /// [`CommandFrame`]: ../resp/struct.CommandFrame.html
+    pub(crate) fn from_frame(frame: &CommandFrame) -> Option<RawCommand> {
+        let mut args = frame.args();
+        let name = String::from_utf8_lossy(args.next()?).to_uppercase();
+        Some(RawCommand {
+            name,
+            args: args.map(<[u8]>::to_vec).collect(),
+        })
+    }
+
+    /// 将此命令解析为[`Command`]，并交由`handler`处理
+    ///
+    /// [`Command`]: enum.Command.html
+    pub fn dispatch(&self, handler: &mut dyn EventHandler) {
+        dispatch(self.name.as_bytes(), Args::Owned(self.args.iter()), handler);
+    }
+}
+
+/// 命令的参数，解析命令时按顺序逐个取出，参数的内容不会被复制
+pub(crate) enum Args<'a> {
+    Frame(FrameArgs<'a>),
+    Owned(Iter<'a, Vec<u8>>),
+}
+
+impl<'a> Iterator for Args<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<&'a [u8]> {
+        match self {
+            Args::Frame(args) => args.next(),
+            Args::Owned(args) => args.next().map(Vec::as_slice),
+        }
+    }
+}
+
+// 最长的已支持命令为ZREMRANGEBYSCORE，超出此长度的命令名无需转换为大写进行匹配
+const MAX_NAME_LEN: usize = 32;
+
+/// 解析以`Vec`保存的命令，第一个元素为命令名
+#[cfg(test)]
 pub(crate) fn parse(data: Vec<Vec<u8>>, cmd_handler: &mut dyn EventHandler) {
     let mut iter = data.iter();
     if let Some(cmd_name) = iter.next() {
-        let cmd_name = String::from_utf8_lossy(cmd_name).to_uppercase();
-        match cmd_name.as_str() {
-            "APPEND" => {
-                let cmd = strings::parse_append(iter);
-                cmd_handler.handle(Event::AOF(Command::APPEND(&cmd)));
-            }
-            "BITFIELD" => {
-                let cmd = strings::parse_bitfield(iter);
-                cmd_handler.handle(Event::AOF(Command::BITFIELD(&cmd)));
-            }
-            "BITOP" => {
-                let cmd = strings::parse_bitop(iter);
-                cmd_handler.handle(Event::AOF(Command::BITOP(&cmd)));
-            }
-            "BRPOPLPUSH" => {
-                let cmd = lists::parse_brpoplpush(iter);
-                cmd_handler.handle(Event::AOF(Command::BRPOPLPUSH(&cmd)));
-            }
-            "DEL" => {
-                let cmd = keys::parse_del(iter);
-                cmd_handler.handle(Event::AOF(Command::DEL(&cmd)));
-            }
-            "DECR" => {
-                let cmd = strings::parse_decr(iter);
-                cmd_handler.handle(Event::AOF(Command::DECR(&cmd)));
-            }
-            "DECRBY" => {
-                let cmd = strings::parse_decrby(iter);
-                cmd_handler.handle(Event::AOF(Command::DECRBY(&cmd)));
-            }
-            "EVAL" => {
-                let cmd = scripting::parse_eval(iter);
-                cmd_handler.handle(Event::AOF(Command::EVAL(&cmd)));
-            }
-            "EVALSHA" => {
-                let cmd = scripting::parse_evalsha(iter);
-                cmd_handler.handle(Event::AOF(Command::EVALSHA(&cmd)));
-            }
-            "EXPIRE" => {
-                let cmd = keys::parse_expire(iter);
-                cmd_handler.handle(Event::AOF(Command::EXPIRE(&cmd)));
-            }
-            "EXPIREAT" => {
-                let cmd = keys::parse_expireat(iter);
-                cmd_handler.handle(Event::AOF(Command::EXPIREAT(&cmd)));
-            }
-            "EXEC" => {
-                cmd_handler.handle(Event::AOF(Command::EXEC));
-            }
-            "FLUSHALL" => {
-                let cmd = server::parse_flushall(iter);
-                cmd_handler.handle(Event::AOF(Command::FLUSHALL(&cmd)));
-            }
-            "FLUSHDB" => {
-                let cmd = server::parse_flushdb(iter);
-                cmd_handler.handle(Event::AOF(Command::FLUSHDB(&cmd)));
-            }
-            "GETSET" => {
-                let cmd = strings::parse_getset(iter);
-                cmd_handler.handle(Event::AOF(Command::GETSET(&cmd)));
-            }
-            "HDEL" => {
-                let cmd = hashes::parse_hdel(iter);
-                cmd_handler.handle(Event::AOF(Command::HDEL(&cmd)));
-            }
-            "HINCRBY" => {
-                let cmd = hashes::parse_hincrby(iter);
-                cmd_handler.handle(Event::AOF(Command::HINCRBY(&cmd)));
-            }
-            "HMSET" => {
-                let cmd = hashes::parse_hmset(iter);
-                cmd_handler.handle(Event::AOF(Command::HMSET(&cmd)));
-            }
-            "HSET" => {
-                let cmd = hashes::parse_hset(iter);
-                cmd_handler.handle(Event::AOF(Command::HSET(&cmd)));
-            }
-            "HSETNX" => {
-                let cmd = hashes::parse_hsetnx(iter);
-                cmd_handler.handle(Event::AOF(Command::HSETNX(&cmd)));
-            }
-            "INCR" => {
-                let cmd = strings::parse_incr(iter);
-                cmd_handler.handle(Event::AOF(Command::INCR(&cmd)));
-            }
-            "INCRBY" => {
-                let cmd = strings::parse_incrby(iter);
-                cmd_handler.handle(Event::AOF(Command::INCRBY(&cmd)));
-            }
-            "LINSERT" => {
-                let cmd = lists::parse_linsert(iter);
-                cmd_handler.handle(Event::AOF(Command::LINSERT(&cmd)));
-            }
-            "LPOP" => {
-                let cmd = lists::parse_lpop(iter);
-                cmd_handler.handle(Event::AOF(Command::LPOP(&cmd)));
-            }
-            "LPUSH" => {
-                let cmd = lists::parse_lpush(iter);
-                cmd_handler.handle(Event::AOF(Command::LPUSH(&cmd)));
-            }
-            "LPUSHX" => {
-                let cmd = lists::parse_lpushx(iter);
-                cmd_handler.handle(Event::AOF(Command::LPUSHX(&cmd)));
-            }
-            "LREM" => {
-                let cmd = lists::parse_lrem(iter);
-                cmd_handler.handle(Event::AOF(Command::LREM(&cmd)));
-            }
-            "LSET" => {
-                let cmd = lists::parse_lset(iter);
-                cmd_handler.handle(Event::AOF(Command::LSET(&cmd)));
-            }
-            "LTRIM" => {
-                let cmd = lists::parse_ltrim(iter);
-                cmd_handler.handle(Event::AOF(Command::LTRIM(&cmd)));
-            }
-            "RENAME" => {
-                let cmd = keys::parse_rename(iter);
-                cmd_handler.handle(Event::AOF(Command::RENAME(&cmd)));
-            }
-            "RENAMENX" => {
-                let cmd = keys::parse_renamenx(iter);
-                cmd_handler.handle(Event::AOF(Command::RENAMENX(&cmd)));
-            }
-            "RESTORE" => {
-                let cmd = keys::parse_restore(iter);
-                cmd_handler.handle(Event::AOF(Command::RESTORE(&cmd)));
-            }
-            "RPOP" => {
-                let cmd = lists::parse_rpop(iter);
-                cmd_handler.handle(Event::AOF(Command::RPOP(&cmd)));
-            }
-            "RPOPLPUSH" => {
-                let cmd = lists::parse_rpoplpush(iter);
-                cmd_handler.handle(Event::AOF(Command::RPOPLPUSH(&cmd)));
-            }
-            "RPUSH" => {
-                let cmd = lists::parse_rpush(iter);
-                cmd_handler.handle(Event::AOF(Command::RPUSH(&cmd)));
-            }
-            "RPUSHX" => {
-                let cmd = lists::parse_rpushx(iter);
-                cmd_handler.handle(Event::AOF(Command::RPUSHX(&cmd)));
-            }
-            "SADD" => {
-                let cmd = sets::parse_sadd(iter);
-                cmd_handler.handle(Event::AOF(Command::SADD(&cmd)));
-            }
-            "SCRIPT" => {
-                let cmd = iter.next().unwrap();
-                let cmd = String::from_utf8_lossy(cmd).to_uppercase();
-                if &cmd == "LOAD" {
-                    let cmd = scripting::parse_script_load(iter);
-                    cmd_handler.handle(Event::AOF(Command::SCRIPTLOAD(&cmd)));
-                } else if &cmd == "FLUSH" {
-                    cmd_handler.handle(Event::AOF(Command::SCRIPTFLUSH));
-                }
-            }
-            "SDIFFSTORE" => {
-                let cmd = sets::parse_sdiffstore(iter);
-                cmd_handler.handle(Event::AOF(Command::SDIFFSTORE(&cmd)));
-            }
-            "SMOVE" => {
-                let cmd = sets::parse_smove(iter);
-                cmd_handler.handle(Event::AOF(Command::SMOVE(&cmd)));
-            }
-            "SET" => {
-                let cmd = strings::parse_set(iter);
-                cmd_handler.handle(Event::AOF(Command::SET(&cmd)));
-            }
-            "SELECT" => {
-                let cmd = connection::parse_select(iter);
-                cmd_handler.handle(Event::AOF(Command::SELECT(&cmd)));
-            }
-            "SORT" => {
-                let cmd = keys::parse_sort(iter);
-                cmd_handler.handle(Event::AOF(Command::SORT(&cmd)));
-            }
-            "SREM" => {
-                let cmd = sets::parse_srem(iter);
-                cmd_handler.handle(Event::AOF(Command::SREM(&cmd)));
-            }
-            "SUNIONSTORE" => {
-                let cmd = sets::parse_sunionstore(iter);
-                cmd_handler.handle(Event::AOF(Command::SUNIONSTORE(&cmd)));
-            }
-            "SWAPDB" => {
-                let cmd = connection::parse_swapdb(iter);
-                cmd_handler.handle(Event::AOF(Command::SWAPDB(&cmd)));
-            }
-            "UNLINK" => {
-                let cmd = keys::parse_unlink(iter);
-                cmd_handler.handle(Event::AOF(Command::UNLINK(&cmd)));
-            }
-            "MOVE" => {
-                let cmd = keys::parse_move(iter);
-                cmd_handler.handle(Event::AOF(Command::MOVE(&cmd)));
-            }
-            "MSET" => {
-                let cmd = strings::parse_mset(iter);
-                cmd_handler.handle(Event::AOF(Command::MSET(&cmd)));
-            }
-            "MSETNX" => {
-                let cmd = strings::parse_msetnx(iter);
-                cmd_handler.handle(Event::AOF(Command::MSETNX(&cmd)));
-            }
-            "MULTI" => {
-                cmd_handler.handle(Event::AOF(Command::MULTI));
-            }
-            "PFADD" => {
-                let cmd = hyperloglog::parse_pfadd(iter);
-                cmd_handler.handle(Event::AOF(Command::PFADD(&cmd)));
-            }
-            "PFCOUNT" => {
-                let cmd = hyperloglog::parse_pfcount(iter);
-                cmd_handler.handle(Event::AOF(Command::PFCOUNT(&cmd)));
-            }
-            "PFMERGE" => {
-                let cmd = hyperloglog::parse_pfmerge(iter);
-                cmd_handler.handle(Event::AOF(Command::PFMERGE(&cmd)));
-            }
-            "SETEX" => {
-                let cmd = strings::parse_setex(iter);
-                cmd_handler.handle(Event::AOF(Command::SETEX(&cmd)));
-            }
-            "SETNX" => {
-                let cmd = strings::parse_setnx(iter);
-                cmd_handler.handle(Event::AOF(Command::SETNX(&cmd)));
-            }
-            "PSETEX" => {
-                let cmd = strings::parse_psetex(iter);
-                cmd_handler.handle(Event::AOF(Command::PSETEX(&cmd)));
-            }
-            "PUBLISH" => {
-                let cmd = pub_sub::parse_publish(iter);
-                cmd_handler.handle(Event::AOF(Command::PUBLISH(&cmd)));
-            }
-            "PEXPIRE" => {
-                let cmd = keys::parse_pexpire(iter);
-                cmd_handler.handle(Event::AOF(Command::PEXPIRE(&cmd)));
-            }
-            "PEXPIREAT" => {
-                let cmd = keys::parse_pexpireat(iter);
-                cmd_handler.handle(Event::AOF(Command::PEXPIREAT(&cmd)));
-            }
-            "PERSIST" => {
-                let cmd = keys::parse_persist(iter);
-                cmd_handler.handle(Event::AOF(Command::PERSIST(&cmd)));
-            }
-            "SETRANGE" => {
-                let cmd = strings::parse_setrange(iter);
-                cmd_handler.handle(Event::AOF(Command::SETRANGE(&cmd)));
-            }
-            "SETBIT" => {
-                let cmd = strings::parse_setbit(iter);
-                cmd_handler.handle(Event::AOF(Command::SETBIT(&cmd)));
-            }
-            "SINTERSTORE" => {
-                let cmd = sets::parse_sinterstore(iter);
-                cmd_handler.handle(Event::AOF(Command::SINTERSTORE(&cmd)));
-            }
-            "ZADD" => {
-                let cmd = sorted_sets::parse_zadd(iter);
-                cmd_handler.handle(Event::AOF(Command::ZADD(&cmd)));
-            }
-            "ZINCRBY" => {
-                let cmd = sorted_sets::parse_zincrby(iter);
-                cmd_handler.handle(Event::AOF(Command::ZINCRBY(&cmd)));
-            }
-            "ZINTERSTORE" => {
-                let cmd = sorted_sets::parse_zinterstore(iter);
-                cmd_handler.handle(Event::AOF(Command::ZINTERSTORE(&cmd)));
-            }
-            "ZPOPMAX" => {
-                let cmd = sorted_sets::parse_zpopmax(iter);
-                cmd_handler.handle(Event::AOF(Command::ZPOPMAX(&cmd)));
-            }
-            "ZPOPMIN" => {
-                let cmd = sorted_sets::parse_zpopmin(iter);
-                cmd_handler.handle(Event::AOF(Command::ZPOPMIN(&cmd)));
-            }
-            "ZREM" => {
-                let cmd = sorted_sets::parse_zrem(iter);
-                cmd_handler.handle(Event::AOF(Command::ZREM(&cmd)));
-            }
-            "ZREMRANGEBYLEX" => {
-                let cmd = sorted_sets::parse_zremrangebylex(iter);
-                cmd_handler.handle(Event::AOF(Command::ZREMRANGEBYLEX(&cmd)));
-            }
-            "ZREMRANGEBYRANK" => {
-                let cmd = sorted_sets::parse_zremrangebyrank(iter);
-                cmd_handler.handle(Event::AOF(Command::ZREMRANGEBYRANK(&cmd)));
-            }
-            "ZREMRANGEBYSCORE" => {
-                let cmd = sorted_sets::parse_zremrangebyscore(iter);
-                cmd_handler.handle(Event::AOF(Command::ZREMRANGEBYSCORE(&cmd)));
-            }
-            "ZUNIONSTORE" => {
-                let cmd = sorted_sets::parse_zunionstore(iter);
-                cmd_handler.handle(Event::AOF(Command::ZUNIONSTORE(&cmd)));
-            }
-            "XACK" => {
-                let cmd = streams::parse_xack(iter);
-                cmd_handler.handle(Event::AOF(Command::XACK(&cmd)));
-            }
-            "XADD" => {
-                let cmd = streams::parse_xadd(iter);
-                cmd_handler.handle(Event::AOF(Command::XADD(&cmd)));
-            }
-            "XCLAIM" => {
-                let cmd = streams::parse_xclaim(iter);
-                cmd_handler.handle(Event::AOF(Command::XCLAIM(&cmd)));
-            }
-            "XDEL" => {
-                let cmd = streams::parse_xdel(iter);
-                cmd_handler.handle(Event::AOF(Command::XDEL(&cmd)));
-            }
-            "XGROUP" => {
-                let cmd = streams::parse_xgroup(iter);
-                cmd_handler.handle(Event::AOF(Command::XGROUP(&cmd)));
-            }
-            "XTRIM" => {
-                let cmd = streams::parse_xtrim(iter);
-                cmd_handler.handle(Event::AOF(Command::XTRIM(&cmd)));
-            }
-            "PING" => {
-                // PING命令是由Redis master主动发送过来，判断下游节点是否活跃，不需要处理
-            }
-            _ => {
-                let mut args = Vec::new();
-                while let Some(arg) = iter.next() {
-                    args.push(arg.clone());
-                }
-                let cmd = RawCommand { name: cmd_name, args };
-                cmd_handler.handle(Event::AOF(Command::Other(cmd)))
-            }
-        };
+        dispatch(cmd_name, Args::Owned(iter), cmd_handler);
     }
 }
+
+/// 根据命令名解析参数，并交由`cmd_handler`处理，命令名不区分大小写
+pub(crate) fn dispatch(name: &[u8], mut iter: Args, cmd_handler: &mut dyn EventHandler) {
+    // 在栈上转换为大写，避免为每条命令分配内存
+    let mut upper = [0; MAX_NAME_LEN];
+    let cmd_name = match upper.get_mut(..name.len()) {
+        Some(upper) => {
+            upper.copy_from_slice(name);
+            upper.make_ascii_uppercase();
+            &*upper
+        }
+        None => name,
+    };
+    match cmd_name {
+        b"APPEND" => {
+            let cmd = strings::parse_append(iter);
+            cmd_handler.handle(Event::AOF(Command::APPEND(&cmd)));
+        }
+        b"BITFIELD" => {
+            let cmd = strings::parse_bitfield(iter);
+            cmd_handler.handle(Event::AOF(Command::BITFIELD(&cmd)));
+        }
+        b"BITOP" => {
+            let cmd = strings::parse_bitop(iter);
+            cmd_handler.handle(Event::AOF(Command::BITOP(&cmd)));
+        }
+        b"BRPOPLPUSH" => {
+            let cmd = lists::parse_brpoplpush(iter);
+            cmd_handler.handle(Event::AOF(Command::BRPOPLPUSH(&cmd)));
+        }
+        b"DEL" => {
+            let cmd = keys::parse_del(iter);
+            cmd_handler.handle(Event::AOF(Command::DEL(&cmd)));
+        }
+        b"DECR" => {
+            let cmd = strings::parse_decr(iter);
+            cmd_handler.handle(Event::AOF(Command::DECR(&cmd)));
+        }
+        b"DECRBY" => {
+            let cmd = strings::parse_decrby(iter);
+            cmd_handler.handle(Event::AOF(Command::DECRBY(&cmd)));
+        }
+        b"EVAL" => {
+            let cmd = scripting::parse_eval(iter);
+            cmd_handler.handle(Event::AOF(Command::EVAL(&cmd)));
+        }
+        b"EVALSHA" => {
+            let cmd = scripting::parse_evalsha(iter);
+            cmd_handler.handle(Event::AOF(Command::EVALSHA(&cmd)));
+        }
+        b"EXPIRE" => {
+            let cmd = keys::parse_expire(iter);
+            cmd_handler.handle(Event::AOF(Command::EXPIRE(&cmd)));
+        }
+        b"EXPIREAT" => {
+            let cmd = keys::parse_expireat(iter);
+            cmd_handler.handle(Event::AOF(Command::EXPIREAT(&cmd)));
+        }
+        b"EXEC" => {
+            cmd_handler.handle(Event::AOF(Command::EXEC));
+        }
+        b"FLUSHALL" => {
+            let cmd = server::parse_flushall(iter);
+            cmd_handler.handle(Event::AOF(Command::FLUSHALL(&cmd)));
+        }
+        b"FLUSHDB" => {
+            let cmd = server::parse_flushdb(iter);
+            cmd_handler.handle(Event::AOF(Command::FLUSHDB(&cmd)));
+        }
+        b"GETSET" => {
+            let cmd = strings::parse_getset(iter);
+            cmd_handler.handle(Event::AOF(Command::GETSET(&cmd)));
+        }
+        b"HDEL" => {
+            let cmd = hashes::parse_hdel(iter);
+            cmd_handler.handle(Event::AOF(Command::HDEL(&cmd)));
+        }
+        b"HINCRBY" => {
+            let cmd = hashes::parse_hincrby(iter);
+            cmd_handler.handle(Event::AOF(Command::HINCRBY(&cmd)));
+        }
+        b"HMSET" => {
+            let cmd = hashes::parse_hmset(iter);
+            cmd_handler.handle(Event::AOF(Command::HMSET(&cmd)));
+        }
+        b"HSET" => {
+            let cmd = hashes::parse_hset(iter);
+            cmd_handler.handle(Event::AOF(Command::HSET(&cmd)));
+        }
+        b"HSETNX" => {
+            let cmd = hashes::parse_hsetnx(iter);
+            cmd_handler.handle(Event::AOF(Command::HSETNX(&cmd)));
+        }
+        b"INCR" => {
+            let cmd = strings::parse_incr(iter);
+            cmd_handler.handle(Event::AOF(Command::INCR(&cmd)));
+        }
+        b"INCRBY" => {
+            let cmd = strings::parse_incrby(iter);
+            cmd_handler.handle(Event::AOF(Command::INCRBY(&cmd)));
+        }
+        b"LINSERT" => {
+            let cmd = lists::parse_linsert(iter);
+            cmd_handler.handle(Event::AOF(Command::LINSERT(&cmd)));
+        }
+        b"LPOP" => {
+            let cmd = lists::parse_lpop(iter);
+            cmd_handler.handle(Event::AOF(Command::LPOP(&cmd)));
+        }
+        b"LPUSH" => {
+            let cmd = lists::parse_lpush(iter);
+            cmd_handler.handle(Event::AOF(Command::LPUSH(&cmd)));
+        }
+        b"LPUSHX" => {
+            let cmd = lists::parse_lpushx(iter);
+            cmd_handler.handle(Event::AOF(Command::LPUSHX(&cmd)));
+        }
+        b"LREM" => {
+            let cmd = lists::parse_lrem(iter);
+            cmd_handler.handle(Event::AOF(Command::LREM(&cmd)));
+        }
+        b"LSET" => {
+            let cmd = lists::parse_lset(iter);
+            cmd_handler.handle(Event::AOF(Command::LSET(&cmd)));
+        }
+        b"LTRIM" => {
+            let cmd = lists::parse_ltrim(iter);
+            cmd_handler.handle(Event::AOF(Command::LTRIM(&cmd)));
+        }
+        b"RENAME" => {
+            let cmd = keys::parse_rename(iter);
+            cmd_handler.handle(Event::AOF(Command::RENAME(&cmd)));
+        }
+        b"RENAMENX" => {
+            let cmd = keys::parse_renamenx(iter);
+            cmd_handler.handle(Event::AOF(Command::RENAMENX(&cmd)));
+        }
+        b"RESTORE" => {
+            let cmd = keys::parse_restore(iter);
+            cmd_handler.handle(Event::AOF(Command::RESTORE(&cmd)));
+        }
+        b"RPOP" => {
+            let cmd = lists::parse_rpop(iter);
+            cmd_handler.handle(Event::AOF(Command::RPOP(&cmd)));
+        }
+        b"RPOPLPUSH" => {
+            let cmd = lists::parse_rpoplpush(iter);
+            cmd_handler.handle(Event::AOF(Command::RPOPLPUSH(&cmd)));
+        }
+        b"RPUSH" => {
+            let cmd = lists::parse_rpush(iter);
+            cmd_handler.handle(Event::AOF(Command::RPUSH(&cmd)));
+        }
+        b"RPUSHX" => {
+            let cmd = lists::parse_rpushx(iter);
+            cmd_handler.handle(Event::AOF(Command::RPUSHX(&cmd)));
+        }
+        b"SADD" => {
+            let cmd = sets::parse_sadd(iter);
+            cmd_handler.handle(Event::AOF(Command::SADD(&cmd)));
+        }
+        b"SCRIPT" => {
+            let cmd = iter.next().unwrap();
+            let cmd = String::from_utf8_lossy(cmd).to_uppercase();
+            if &cmd == "LOAD" {
+                let cmd = scripting::parse_script_load(iter);
+                cmd_handler.handle(Event::AOF(Command::SCRIPTLOAD(&cmd)));
+            } else if &cmd == "FLUSH" {
+                cmd_handler.handle(Event::AOF(Command::SCRIPTFLUSH));
+            }
+        }
+        b"SDIFFSTORE" => {
+            let cmd = sets::parse_sdiffstore(iter);
+            cmd_handler.handle(Event::AOF(Command::SDIFFSTORE(&cmd)));
+        }
+        b"SMOVE" => {
+            let cmd = sets::parse_smove(iter);
+            cmd_handler.handle(Event::AOF(Command::SMOVE(&cmd)));
+        }
+        b"SET" => {
+            let cmd = strings::parse_set(iter);
+            cmd_handler.handle(Event::AOF(Command::SET(&cmd)));
+        }
+        b"SELECT" => {
+            let cmd = connection::parse_select(iter);
+            cmd_handler.handle(Event::AOF(Command::SELECT(&cmd)));
+        }
+        b"SORT" => {
+            let cmd = keys::parse_sort(iter);
+            cmd_handler.handle(Event::AOF(Command::SORT(&cmd)));
+        }
+        b"SREM" => {
+            let cmd = sets::parse_srem(iter);
+            cmd_handler.handle(Event::AOF(Command::SREM(&cmd)));
+        }
+        b"SUNIONSTORE" => {
+            let cmd = sets::parse_sunionstore(iter);
+            cmd_handler.handle(Event::AOF(Command::SUNIONSTORE(&cmd)));
+        }
+        b"SWAPDB" => {
+            let cmd = connection::parse_swapdb(iter);
+            cmd_handler.handle(Event::AOF(Command::SWAPDB(&cmd)));
+        }
+        b"UNLINK" => {
+            let cmd = keys::parse_unlink(iter);
+            cmd_handler.handle(Event::AOF(Command::UNLINK(&cmd)));
+        }
+        b"MOVE" => {
+            let cmd = keys::parse_move(iter);
+            cmd_handler.handle(Event::AOF(Command::MOVE(&cmd)));
+        }
+        b"MSET" => {
+            let cmd = strings::parse_mset(iter);
+            cmd_handler.handle(Event::AOF(Command::MSET(&cmd)));
+        }
+        b"MSETNX" => {
+            let cmd = strings::parse_msetnx(iter);
+            cmd_handler.handle(Event::AOF(Command::MSETNX(&cmd)));
+        }
+        b"MULTI" => {
+            cmd_handler.handle(Event::AOF(Command::MULTI));
+        }
+        b"PFADD" => {
+            let cmd = hyperloglog::parse_pfadd(iter);
+            cmd_handler.handle(Event::AOF(Command::PFADD(&cmd)));
+        }
+        b"PFCOUNT" => {
+            let cmd = hyperloglog::parse_pfcount(iter);
+            cmd_handler.handle(Event::AOF(Command::PFCOUNT(&cmd)));
+        }
+        b"PFMERGE" => {
+            let cmd = hyperloglog::parse_pfmerge(iter);
+            cmd_handler.handle(Event::AOF(Command::PFMERGE(&cmd)));
+        }
+        b"SETEX" => {
+            let cmd = strings::parse_setex(iter);
+            cmd_handler.handle(Event::AOF(Command::SETEX(&cmd)));
+        }
+        b"SETNX" => {
+            let cmd = strings::parse_setnx(iter);
+            cmd_handler.handle(Event::AOF(Command::SETNX(&cmd)));
+        }
+        b"PSETEX" => {
+            let cmd = strings::parse_psetex(iter);
+            cmd_handler.handle(Event::AOF(Command::PSETEX(&cmd)));
+        }
+        b"PUBLISH" => {
+            let cmd = pub_sub::parse_publish(iter);
+            cmd_handler.handle(Event::AOF(Command::PUBLISH(&cmd)));
+        }
+        b"PEXPIRE" => {
+            let cmd = keys::parse_pexpire(iter);
+            cmd_handler.handle(Event::AOF(Command::PEXPIRE(&cmd)));
+        }
+        b"PEXPIREAT" => {
+            let cmd = keys::parse_pexpireat(iter);
+            cmd_handler.handle(Event::AOF(Command::PEXPIREAT(&cmd)));
+        }
+        b"PERSIST" => {
+            let cmd = keys::parse_persist(iter);
+            cmd_handler.handle(Event::AOF(Command::PERSIST(&cmd)));
+        }
+        b"SETRANGE" => {
+            let cmd = strings::parse_setrange(iter);
+            cmd_handler.handle(Event::AOF(Command::SETRANGE(&cmd)));
+        }
+        b"SETBIT" => {
+            let cmd = strings::parse_setbit(iter);
+            cmd_handler.handle(Event::AOF(Command::SETBIT(&cmd)));
+        }
+        b"SINTERSTORE" => {
+            let cmd = sets::parse_sinterstore(iter);
+            cmd_handler.handle(Event::AOF(Command::SINTERSTORE(&cmd)));
+        }
+        b"ZADD" => {
+            let cmd = sorted_sets::parse_zadd(iter);
+            cmd_handler.handle(Event::AOF(Command::ZADD(&cmd)));
+        }
+        b"ZINCRBY" => {
+            let cmd = sorted_sets::parse_zincrby(iter);
+            cmd_handler.handle(Event::AOF(Command::ZINCRBY(&cmd)));
+        }
+        b"ZINTERSTORE" => {
+            let cmd = sorted_sets::parse_zinterstore(iter);
+            cmd_handler.handle(Event::AOF(Command::ZINTERSTORE(&cmd)));
+        }
+        b"ZPOPMAX" => {
+            let cmd = sorted_sets::parse_zpopmax(iter);
+            cmd_handler.handle(Event::AOF(Command::ZPOPMAX(&cmd)));
+        }
+        b"ZPOPMIN" => {
+            let cmd = sorted_sets::parse_zpopmin(iter);
+            cmd_handler.handle(Event::AOF(Command::ZPOPMIN(&cmd)));
+        }
+        b"ZREM" => {
+            let cmd = sorted_sets::parse_zrem(iter);
+            cmd_handler.handle(Event::AOF(Command::ZREM(&cmd)));
+        }
+        b"ZREMRANGEBYLEX" => {
+            let cmd = sorted_sets::parse_zremrangebylex(iter);
+            cmd_handler.handle(Event::AOF(Command::ZREMRANGEBYLEX(&cmd)));
+        }
+        b"ZREMRANGEBYRANK" => {
+            let cmd = sorted_sets::parse_zremrangebyrank(iter);
+            cmd_handler.handle(Event::AOF(Command::ZREMRANGEBYRANK(&cmd)));
+        }
+        b"ZREMRANGEBYSCORE" => {
+            let cmd = sorted_sets::parse_zremrangebyscore(iter);
+            cmd_handler.handle(Event::AOF(Command::ZREMRANGEBYSCORE(&cmd)));
+        }
+        b"ZUNIONSTORE" => {
+            let cmd = sorted_sets::parse_zunionstore(iter);
+            cmd_handler.handle(Event::AOF(Command::ZUNIONSTORE(&cmd)));
+        }
+        b"XACK" => {
+            let cmd = streams::parse_xack(iter);
+            cmd_handler.handle(Event::AOF(Command::XACK(&cmd)));
+        }
+        b"XADD" => {
+            let cmd = streams::parse_xadd(iter);
+            cmd_handler.handle(Event::AOF(Command::XADD(&cmd)));
+        }
+        b"XCLAIM" => {
+            let cmd = streams::parse_xclaim(iter);
+            cmd_handler.handle(Event::AOF(Command::XCLAIM(&cmd)));
+        }
+        b"XDEL" => {
+            let cmd = streams::parse_xdel(iter);
+            cmd_handler.handle(Event::AOF(Command::XDEL(&cmd)));
+        }
+        b"XGROUP" => {
+            let cmd = streams::parse_xgroup(iter);
+            cmd_handler.handle(Event::AOF(Command::XGROUP(&cmd)));
+        }
+        b"XTRIM" => {
+            let cmd = streams::parse_xtrim(iter);
+            cmd_handler.handle(Event::AOF(Command::XTRIM(&cmd)));
+        }
+        b"PING" => {
+            // PING命令是由Redis master主动发送过来，判断下游节点是否活跃，不需要处理
+        }
+        b"REPLCONF" => {
+            // REPLCONF命令是master与replica之间的控制命令(如REPLCONF GETACK)，由listener负责应答，不需要处理
+        }
+        _ => {
+            let cmd = RawCommand {
+                name: to_string(name.to_vec()).to_uppercase(),
+                args: iter.map(<[u8]>::to_vec).collect(),
+            };
+            cmd_handler.handle(Event::AOF(Command::Other(cmd)))
+        }
+    };
+}
@@ -6,7 +6,7 @@ Pub/Sub相关的命令定义、解析
 [Redis Command Reference]: https://redis.io/commands#pubsub
 */
 
-use std::slice::Iter;
+use crate::cmd::Args;
 
 #[derive(Debug)]
 pub struct PUBLISH<'a> {
@@ -14,7 +14,7 @@ pub struct PUBLISH<'a> {
     pub message: &'a [u8],
 }
 
-pub(crate) fn parse_publish(mut iter: Iter<Vec<u8>>) -> PUBLISH {
+pub(crate) fn parse_publish(mut iter: Args) -> PUBLISH {
     let channel = iter.next().unwrap();
     let message = iter.next().unwrap();
     PUBLISH { channel, message }
@@ -6,7 +6,7 @@ Scripting相关的命令定义、解析
 [Redis Command Reference]: https://redis.io/commands#scripting
 */
 
-use std::slice::Iter;
+use crate::cmd::Args;
 
 #[derive(Debug)]
 pub struct EVAL<'a> {
@@ -16,18 +16,18 @@ pub struct EVAL<'a> {
     pub args: Vec<&'a [u8]>,
 }
 
-pub(crate) fn parse_eval(mut iter: Iter<Vec<u8>>) -> EVAL {
+pub(crate) fn parse_eval(mut iter: Args) -> EVAL {
     let script = iter.next().unwrap();
     let num_keys = iter.next().unwrap();
     let num_keys = String::from_utf8_lossy(num_keys).parse::<i32>().unwrap();
     let mut keys = Vec::with_capacity(num_keys as usize);
     for _ in 0..num_keys {
         let key = iter.next().unwrap();
-        keys.push(key.as_slice());
+        keys.push(key);
     }
     let mut args = Vec::new();
     while let Some(arg) = iter.next() {
-        args.push(arg.as_slice());
+        args.push(arg);
     }
     EVAL {
         script,
@@ -45,18 +45,18 @@ pub struct EVALSHA<'a> {
     pub args: Vec<&'a [u8]>,
 }
 
-pub(crate) fn parse_evalsha(mut iter: Iter<Vec<u8>>) -> EVALSHA {
+pub(crate) fn parse_evalsha(mut iter: Args) -> EVALSHA {
     let sha1 = iter.next().unwrap();
     let num_keys = iter.next().unwrap();
     let num_keys = String::from_utf8_lossy(num_keys).parse::<i32>().unwrap();
     let mut keys = Vec::with_capacity(num_keys as usize);
     for _ in 0..num_keys {
         let key = iter.next().unwrap();
-        keys.push(key.as_slice());
+        keys.push(key);
     }
     let mut args = Vec::new();
     while let Some(arg) = iter.next() {
-        args.push(arg.as_slice());
+        args.push(arg);
     }
     EVALSHA {
         sha1,
@@ -71,7 +71,7 @@ pub struct SCRIPTLOAD<'a> {
     pub script: &'a [u8],
 }
 
-pub(crate) fn parse_script_load(mut iter: Iter<Vec<u8>>) -> SCRIPTLOAD {
+pub(crate) fn parse_script_load(mut iter: Args) -> SCRIPTLOAD {
     let script = iter.next().unwrap();
     SCRIPTLOAD { script }
 }
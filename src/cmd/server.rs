@@ -6,14 +6,14 @@ Server相关的命令定义、解析
 [Redis Command Reference]: https://redis.io/commands#server
 */
 
-use std::slice::Iter;
+use crate::cmd::Args;
 
 #[derive(Debug)]
 pub struct FLUSHDB {
     pub _async: Option<bool>,
 }
 
-pub(crate) fn parse_flushdb(mut iter: Iter<Vec<u8>>) -> FLUSHDB {
+pub(crate) fn parse_flushdb(mut iter: Args) -> FLUSHDB {
     let mut _async = None;
     if let Some(next_arg) = iter.next() {
         let arg_upper = String::from_utf8_lossy(next_arg).to_uppercase();
@@ -31,7 +31,7 @@ pub struct FLUSHALL {
     pub _async: Option<bool>,
 }
 
-pub(crate) fn parse_flushall(mut iter: Iter<Vec<u8>>) -> FLUSHALL {
+pub(crate) fn parse_flushall(mut iter: Args) -> FLUSHALL {
     let mut _async = None;
     if let Some(next_arg) = iter.next() {
         let arg_upper = String::from_utf8_lossy(next_arg).to_uppercase();
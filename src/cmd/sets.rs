@@ -6,7 +6,7 @@ Sets相关的命令定义、解析
 [Redis Command Reference]: https://redis.io/commands#set
 */
 
-use std::slice::Iter;
+use crate::cmd::Args;
 
 #[derive(Debug)]
 pub struct SINTERSTORE<'a> {
@@ -14,11 +14,11 @@ pub struct SINTERSTORE<'a> {
     pub keys: Vec<&'a [u8]>,
 }
 
-pub(crate) fn parse_sinterstore(mut iter: Iter<Vec<u8>>) -> SINTERSTORE {
+pub(crate) fn parse_sinterstore(mut iter: Args) -> SINTERSTORE {
     let destination = iter.next().unwrap();
     let mut keys = Vec::new();
     for next_arg in iter {
-        keys.push(next_arg.as_slice());
+        keys.push(next_arg);
     }
     SINTERSTORE { destination, keys }
 }
@@ -29,11 +29,11 @@ pub struct SADD<'a> {
     pub members: Vec<&'a [u8]>,
 }
 
-pub(crate) fn parse_sadd(mut iter: Iter<Vec<u8>>) -> SADD {
+pub(crate) fn parse_sadd(mut iter: Args) -> SADD {
     let key = iter.next().unwrap();
     let mut members = Vec::new();
     while let Some(member) = iter.next() {
-        members.push(member.as_slice());
+        members.push(member);
     }
     SADD { key, members }
 }
@@ -44,11 +44,11 @@ pub struct SDIFFSTORE<'a> {
     pub keys: Vec<&'a [u8]>,
 }
 
-pub(crate) fn parse_sdiffstore(mut iter: Iter<Vec<u8>>) -> SDIFFSTORE {
+pub(crate) fn parse_sdiffstore(mut iter: Args) -> SDIFFSTORE {
     let destination = iter.next().unwrap();
     let mut keys = Vec::new();
     while let Some(key) = iter.next() {
-        keys.push(key.as_slice());
+        keys.push(key);
     }
     SDIFFSTORE { destination, keys }
 }
@@ -60,7 +60,7 @@ pub struct SMOVE<'a> {
     pub member: &'a [u8],
 }
 
-pub(crate) fn parse_smove(mut iter: Iter<Vec<u8>>) -> SMOVE {
+pub(crate) fn parse_smove(mut iter: Args) -> SMOVE {
     let source = iter.next().unwrap();
     let destination = iter.next().unwrap();
     let member = iter.next().unwrap();
@@ -77,11 +77,11 @@ pub struct SREM<'a> {
     pub members: Vec<&'a [u8]>,
 }
 
-pub(crate) fn parse_srem(mut iter: Iter<Vec<u8>>) -> SREM {
+pub(crate) fn parse_srem(mut iter: Args) -> SREM {
     let key = iter.next().unwrap();
     let mut members = Vec::new();
     while let Some(member) = iter.next() {
-        members.push(member.as_slice());
+        members.push(member);
     }
     SREM { key, members }
 }
@@ -92,11 +92,11 @@ pub struct SUNIONSTORE<'a> {
     pub keys: Vec<&'a [u8]>,
 }
 
-pub(crate) fn parse_sunionstore(mut iter: Iter<Vec<u8>>) -> SUNIONSTORE {
+pub(crate) fn parse_sunionstore(mut iter: Args) -> SUNIONSTORE {
     let destination = iter.next().unwrap();
     let mut keys = Vec::new();
     for next_arg in iter {
-        keys.push(next_arg.as_slice());
+        keys.push(next_arg);
     }
     SUNIONSTORE { destination, keys }
 }
@@ -6,7 +6,7 @@ Sorted Sets相关的命令定义、解析
 [Redis Command Reference]: https://redis.io/commands#sorted_set
 */
 
-use std::slice::Iter;
+use crate::cmd::Args;
 
 use crate::cmd::sorted_sets::AGGREGATE::{MAX, MIN, SUM};
 use crate::cmd::strings::ExistType;
@@ -29,7 +29,7 @@ pub struct Item<'a> {
     pub member: &'a [u8],
 }
 
-pub(crate) fn parse_zadd(mut iter: Iter<Vec<u8>>) -> ZADD {
+pub(crate) fn parse_zadd(mut iter: Args) -> ZADD {
     let key = iter.next().unwrap();
     let mut exist_type = None;
     let mut ch = None;
@@ -70,7 +70,7 @@ pub struct ZINCRBY<'a> {
     pub member: &'a [u8],
 }
 
-pub(crate) fn parse_zincrby(mut iter: Iter<Vec<u8>>) -> ZINCRBY {
+pub(crate) fn parse_zincrby(mut iter: Args) -> ZINCRBY {
     let key = iter.next().unwrap();
     let increment = iter.next().unwrap();
     let member = iter.next().unwrap();
@@ -93,14 +93,14 @@ pub enum AGGREGATE {
     MAX,
 }
 
-pub(crate) fn parse_zinterstore(mut iter: Iter<Vec<u8>>) -> ZINTERSTORE {
+pub(crate) fn parse_zinterstore(mut iter: Args) -> ZINTERSTORE {
     let destination = iter.next().unwrap();
     let num_keys = String::from_utf8_lossy(iter.next().unwrap());
     let num_keys = num_keys.parse::<i32>().unwrap();
     let mut keys = Vec::new();
     for _ in 0..num_keys {
         let next_key = iter.next().unwrap();
-        keys.push(next_key.as_slice());
+        keys.push(next_key);
     }
     let mut _weights = Vec::new();
     let mut aggregate = None;
@@ -115,7 +115,7 @@ pub(crate) fn parse_zinterstore(mut iter: Iter<Vec<u8>>) -> ZINTERSTORE {
         } else if &arg_upper == "MAX" {
             aggregate = Some(MAX);
         } else {
-            _weights.push(next_arg.as_slice());
+            _weights.push(next_arg);
         }
     }
     let weights;
@@ -139,11 +139,11 @@ pub struct ZPOPMAX<'a> {
     pub count: Option<&'a [u8]>,
 }
 
-pub(crate) fn parse_zpopmax(mut iter: Iter<Vec<u8>>) -> ZPOPMAX {
+pub(crate) fn parse_zpopmax(mut iter: Args) -> ZPOPMAX {
     let key = iter.next().unwrap();
     let mut count = None;
     if let Some(next_arg) = iter.next() {
-        count = Some(next_arg.as_slice());
+        count = Some(next_arg);
     }
     ZPOPMAX { key, count }
 }
@@ -154,11 +154,11 @@ pub struct ZPOPMIN<'a> {
     pub count: Option<&'a [u8]>,
 }
 
-pub(crate) fn parse_zpopmin(mut iter: Iter<Vec<u8>>) -> ZPOPMIN {
+pub(crate) fn parse_zpopmin(mut iter: Args) -> ZPOPMIN {
     let key = iter.next().unwrap();
     let mut count = None;
     if let Some(next_arg) = iter.next() {
-        count = Some(next_arg.as_slice());
+        count = Some(next_arg);
     }
     ZPOPMIN { key, count }
 }
@@ -169,11 +169,11 @@ pub struct ZREM<'a> {
     pub members: Vec<&'a [u8]>,
 }
 
-pub(crate) fn parse_zrem(mut iter: Iter<Vec<u8>>) -> ZREM {
+pub(crate) fn parse_zrem(mut iter: Args) -> ZREM {
     let key = iter.next().unwrap();
     let mut members = Vec::new();
     while let Some(next_arg) = iter.next() {
-        members.push(next_arg.as_slice());
+        members.push(next_arg);
     }
     ZREM { key, members }
 }
@@ -185,7 +185,7 @@ pub struct ZREMRANGEBYLEX<'a> {
     pub max: &'a [u8],
 }
 
-pub(crate) fn parse_zremrangebylex(mut iter: Iter<Vec<u8>>) -> ZREMRANGEBYLEX {
+pub(crate) fn parse_zremrangebylex(mut iter: Args) -> ZREMRANGEBYLEX {
     let key = iter.next().unwrap();
     let min = iter.next().unwrap();
     let max = iter.next().unwrap();
@@ -199,7 +199,7 @@ pub struct ZREMRANGEBYRANK<'a> {
     pub stop: &'a [u8],
 }
 
-pub(crate) fn parse_zremrangebyrank(mut iter: Iter<Vec<u8>>) -> ZREMRANGEBYRANK {
+pub(crate) fn parse_zremrangebyrank(mut iter: Args) -> ZREMRANGEBYRANK {
     let key = iter.next().unwrap();
     let start = iter.next().unwrap();
     let stop = iter.next().unwrap();
@@ -213,7 +213,7 @@ pub struct ZREMRANGEBYSCORE<'a> {
     pub max: &'a [u8],
 }
 
-pub(crate) fn parse_zremrangebyscore(mut iter: Iter<Vec<u8>>) -> ZREMRANGEBYSCORE {
+pub(crate) fn parse_zremrangebyscore(mut iter: Args) -> ZREMRANGEBYSCORE {
     let key = iter.next().unwrap();
     let min = iter.next().unwrap();
     let max = iter.next().unwrap();
@@ -229,14 +229,14 @@ pub struct ZUNIONSTORE<'a> {
     pub aggregate: Option<AGGREGATE>,
 }
 
-pub(crate) fn parse_zunionstore(mut iter: Iter<Vec<u8>>) -> ZUNIONSTORE {
+pub(crate) fn parse_zunionstore(mut iter: Args) -> ZUNIONSTORE {
     let destination = iter.next().unwrap();
     let num_keys = String::from_utf8_lossy(iter.next().unwrap());
     let num_keys = num_keys.parse::<i32>().unwrap();
     let mut keys = Vec::new();
     for _ in 0..num_keys {
         let next_key = iter.next().unwrap();
-        keys.push(next_key.as_slice());
+        keys.push(next_key);
     }
     let mut _weights = Vec::new();
     let mut aggregate = None;
@@ -251,7 +251,7 @@ pub(crate) fn parse_zunionstore(mut iter: Iter<Vec<u8>>) -> ZUNIONSTORE {
         } else if &arg_upper == "MAX" {
             aggregate = Some(MAX);
         } else {
-            _weights.push(next_arg.as_slice());
+            _weights.push(next_arg);
         }
     }
     let weights;
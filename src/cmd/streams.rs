@@ -6,7 +6,7 @@ Stream相关的命令定义、解析
 [Redis Command Reference]: https://redis.io/commands#stream
 */
 
-use core::slice::Iter;
+use crate::cmd::Args;
 
 use crate::cmd::hashes::Field;
 
@@ -14,10 +14,10 @@ use crate::cmd::hashes::Field;
 pub struct XACK<'a> {
     pub key: &'a [u8],
     pub group: &'a [u8],
-    pub ids: Vec<&'a Vec<u8>>,
+    pub ids: Vec<&'a [u8]>,
 }
 
-pub(crate) fn parse_xack(mut iter: Iter<Vec<u8>>) -> XACK {
+pub(crate) fn parse_xack(mut iter: Args) -> XACK {
     let key = iter.next().unwrap();
     let group = iter.next().unwrap();
     let mut ids = Vec::new();
@@ -34,7 +34,7 @@ pub struct XADD<'a> {
     pub fields: Vec<Field<'a>>,
 }
 
-pub(crate) fn parse_xadd(mut iter: Iter<Vec<u8>>) -> XADD {
+pub(crate) fn parse_xadd(mut iter: Args) -> XADD {
     let key = iter.next().unwrap();
     let id = iter.next().unwrap();
     let mut fields = Vec::new();
@@ -59,15 +59,15 @@ pub struct XCLAIM<'a> {
     pub group: &'a [u8],
     pub consumer: &'a [u8],
     pub min_idle_time: &'a [u8],
-    pub ids: Vec<&'a Vec<u8>>,
-    pub idle: Option<&'a Vec<u8>>,
-    pub time: Option<&'a Vec<u8>>,
-    pub retry_count: Option<&'a Vec<u8>>,
+    pub ids: Vec<&'a [u8]>,
+    pub idle: Option<&'a [u8]>,
+    pub time: Option<&'a [u8]>,
+    pub retry_count: Option<&'a [u8]>,
     pub force: Option<bool>,
     pub just_id: Option<bool>,
 }
 
-pub(crate) fn parse_xclaim(mut iter: Iter<Vec<u8>>) -> XCLAIM {
+pub(crate) fn parse_xclaim(mut iter: Args) -> XCLAIM {
     let key = iter.next().unwrap();
     let group = iter.next().unwrap();
     let consumer = iter.next().unwrap();
@@ -117,10 +117,10 @@ pub(crate) fn parse_xclaim(mut iter: Iter<Vec<u8>>) -> XCLAIM {
 #[derive(Debug)]
 pub struct XDEL<'a> {
     pub key: &'a [u8],
-    pub ids: Vec<&'a Vec<u8>>,
+    pub ids: Vec<&'a [u8]>,
 }
 
-pub(crate) fn parse_xdel(mut iter: Iter<Vec<u8>>) -> XDEL {
+pub(crate) fn parse_xdel(mut iter: Args) -> XDEL {
     let key = iter.next().unwrap();
     let mut ids = Vec::new();
     for id in iter {
@@ -164,7 +164,7 @@ pub struct DelConsumer<'a> {
     pub consumer_name: &'a [u8],
 }
 
-pub(crate) fn parse_xgroup(mut iter: Iter<Vec<u8>>) -> XGROUP {
+pub(crate) fn parse_xgroup(mut iter: Args) -> XGROUP {
     let mut create = None;
     let mut set_id = None;
     let mut destroy = None;
@@ -212,7 +212,7 @@ pub struct XTRIM<'a> {
     pub count: u64,
 }
 
-pub(crate) fn parse_xtrim(mut iter: Iter<Vec<u8>>) -> XTRIM {
+pub(crate) fn parse_xtrim(mut iter: Args) -> XTRIM {
     let key = iter.next().unwrap();
     iter.next().unwrap();
     let third = iter.next().unwrap();
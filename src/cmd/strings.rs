@@ -6,7 +6,7 @@ Strings相关的命令定义、解析
 [Redis Command Reference]: https://redis.io/commands#string
 */
 
-use core::slice::Iter;
+use crate::cmd::Args;
 
 use crate::cmd::strings::Op::{AND, NOT, OR, XOR};
 
@@ -16,7 +16,7 @@ pub struct APPEND<'a> {
     pub value: &'a [u8],
 }
 
-pub(crate) fn parse_append(mut iter: Iter<Vec<u8>>) -> APPEND {
+pub(crate) fn parse_append(mut iter: Args) -> APPEND {
     let key = iter.next().unwrap();
     let value = iter.next().unwrap();
     APPEND { key, value }
@@ -63,7 +63,7 @@ pub enum Overflow {
     FAIL,
 }
 
-pub(crate) fn parse_bitfield(mut iter: Iter<Vec<u8>>) -> BITFIELD {
+pub(crate) fn parse_bitfield(mut iter: Args) -> BITFIELD {
     let key = iter.next().unwrap();
 
     let mut statements = Vec::new();
@@ -124,7 +124,7 @@ pub(crate) fn parse_bitfield(mut iter: Iter<Vec<u8>>) -> BITFIELD {
 pub struct BITOP<'a> {
     pub operation: Op,
     pub dest_key: &'a [u8],
-    pub keys: Vec<&'a Vec<u8>>,
+    pub keys: Vec<&'a [u8]>,
 }
 
 #[derive(Debug)]
@@ -135,7 +135,7 @@ pub enum Op {
     NOT,
 }
 
-pub(crate) fn parse_bitop(mut iter: Iter<Vec<u8>>) -> BITOP {
+pub(crate) fn parse_bitop(mut iter: Args) -> BITOP {
     let operation;
     let op = String::from_utf8_lossy(iter.next().unwrap()).to_uppercase();
     if &op == "AND" {
@@ -169,7 +169,7 @@ pub(crate) fn parse_bitop(mut iter: Iter<Vec<u8>>) -> BITOP {
 pub struct SET<'a> {
     pub key: &'a [u8],
     pub value: &'a [u8],
-    pub expire: Option<(ExpireType, &'a Vec<u8>)>,
+    pub expire: Option<(ExpireType, &'a [u8])>,
     pub exist_type: Option<ExistType>,
     pub keep_ttl: Option<bool>,
 }
@@ -190,7 +190,7 @@ pub enum ExistType {
     XX,
 }
 
-pub(crate) fn parse_set(mut iter: Iter<Vec<u8>>) -> SET {
+pub(crate) fn parse_set(mut iter: Args) -> SET {
     let key = iter.next().unwrap();
 
     let value = iter.next().unwrap();
@@ -238,7 +238,7 @@ pub struct SETEX<'a> {
     pub value: &'a [u8],
 }
 
-pub(crate) fn parse_setex(mut iter: Iter<Vec<u8>>) -> SETEX {
+pub(crate) fn parse_setex(mut iter: Args) -> SETEX {
     let key = iter.next().unwrap();
     let seconds = iter.next().unwrap();
     let value = iter.next().unwrap();
@@ -251,7 +251,7 @@ pub struct SETNX<'a> {
     pub value: &'a [u8],
 }
 
-pub(crate) fn parse_setnx(mut iter: Iter<Vec<u8>>) -> SETNX {
+pub(crate) fn parse_setnx(mut iter: Args) -> SETNX {
     let key = iter.next().unwrap();
     let value = iter.next().unwrap();
     SETNX { key, value }
@@ -264,7 +264,7 @@ pub struct PSETEX<'a> {
     pub value: &'a [u8],
 }
 
-pub(crate) fn parse_psetex(mut iter: Iter<Vec<u8>>) -> PSETEX {
+pub(crate) fn parse_psetex(mut iter: Args) -> PSETEX {
     let key = iter.next().unwrap();
     let milliseconds = iter.next().unwrap();
     let value = iter.next().unwrap();
@@ -282,7 +282,7 @@ pub struct SETRANGE<'a> {
     pub value: &'a [u8],
 }
 
-pub(crate) fn parse_setrange(mut iter: Iter<Vec<u8>>) -> SETRANGE {
+pub(crate) fn parse_setrange(mut iter: Args) -> SETRANGE {
     let key = iter.next().unwrap();
     let offset = iter.next().unwrap();
     let value = iter.next().unwrap();
@@ -294,7 +294,7 @@ pub struct DECR<'a> {
     pub key: &'a [u8],
 }
 
-pub(crate) fn parse_decr(mut iter: Iter<Vec<u8>>) -> DECR {
+pub(crate) fn parse_decr(mut iter: Args) -> DECR {
     let key = iter.next().unwrap();
     DECR { key }
 }
@@ -305,7 +305,7 @@ pub struct DECRBY<'a> {
     pub decrement: &'a [u8],
 }
 
-pub(crate) fn parse_decrby(mut iter: Iter<Vec<u8>>) -> DECRBY {
+pub(crate) fn parse_decrby(mut iter: Args) -> DECRBY {
     let key = iter.next().unwrap();
     let decrement = iter.next().unwrap();
     DECRBY { key, decrement }
@@ -316,7 +316,7 @@ pub struct INCR<'a> {
     pub key: &'a [u8],
 }
 
-pub(crate) fn parse_incr(mut iter: Iter<Vec<u8>>) -> INCR {
+pub(crate) fn parse_incr(mut iter: Args) -> INCR {
     let key = iter.next().unwrap();
     INCR { key }
 }
@@ -327,7 +327,7 @@ pub struct INCRBY<'a> {
     pub increment: &'a [u8],
 }
 
-pub(crate) fn parse_incrby(mut iter: Iter<Vec<u8>>) -> INCRBY {
+pub(crate) fn parse_incrby(mut iter: Args) -> INCRBY {
     let key = iter.next().unwrap();
     let increment = iter.next().unwrap();
     INCRBY { key, increment }
@@ -344,7 +344,7 @@ pub struct MSET<'a> {
     pub key_values: Vec<KeyValue<'a>>,
 }
 
-pub(crate) fn parse_mset(mut iter: Iter<Vec<u8>>) -> MSET {
+pub(crate) fn parse_mset(mut iter: Args) -> MSET {
     let mut key_values = Vec::new();
     while let Some(key) = iter.next() {
         if let Some(value) = iter.next() {
@@ -362,7 +362,7 @@ pub struct MSETNX<'a> {
     pub key_values: Vec<KeyValue<'a>>,
 }
 
-pub(crate) fn parse_msetnx(mut iter: Iter<Vec<u8>>) -> MSETNX {
+pub(crate) fn parse_msetnx(mut iter: Args) -> MSETNX {
     let mut key_values = Vec::new();
     while let Some(key) = iter.next() {
         if let Some(value) = iter.next() {
@@ -382,7 +382,7 @@ pub struct SETBIT<'a> {
     pub value: &'a [u8],
 }
 
-pub(crate) fn parse_setbit(mut iter: Iter<Vec<u8>>) -> SETBIT {
+pub(crate) fn parse_setbit(mut iter: Args) -> SETBIT {
     let key = iter.next().unwrap();
     let offset = iter.next().unwrap();
     let value = iter.next().unwrap();
@@ -395,7 +395,7 @@ pub struct GETSET<'a> {
     pub value: &'a [u8],
 }
 
-pub(crate) fn parse_getset(mut iter: Iter<Vec<u8>>) -> GETSET {
+pub(crate) fn parse_getset(mut iter: Args) -> GETSET {
     let key = iter.next().unwrap();
     let value = iter.next().unwrap();
     GETSET { key, value }
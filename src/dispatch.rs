@@ -0,0 +1,277 @@
+/*!
+按key将事件分发到多个线程中并行处理
+
+[`PartitionedHandler`]根据事件中的key(RDB数据的key，或命令中的key)将事件分发到固定数量的分区，
+每个分区由一个线程按顺序处理，因此同一个key的事件的顺序保持不变。分区的计算与Redis Cluster的slot相同，
+支持hash tag，即`{user}.name`与`{user}.age`总是属于同一个分区。
+
+不含key的事件(如`FLUSHALL`、`SWAPDB`、`MULTI`、`EXEC`以及RDB的开始与结束)，
+key属于不同分区的事件(如`MSET`、`RENAME`)，以及无法确定key的未解析命令，将作为屏障处理: 等待所有分区处理完此前的事件之后，
+由其中一个分区单独处理此事件，处理完毕之后所有分区才继续处理之后的事件。`SELECT`会发送到每一个分区。
+
+[`PartitionedHandler`]: struct.PartitionedHandler.html
+*/
+
+use std::io::{Error, Result};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, SyncSender};
+use std::sync::{Arc, Barrier};
+use std::thread::{self, JoinHandle};
+
+use log::{error, warn};
+
+use crate::cluster::{event_keys, slot_of};
+use crate::cmd::{Command, RawCommand};
+use crate::rdb::OwnedObject;
+use crate::resp::CommandFrame;
+use crate::{Event, EventHandler, OwnedEvent};
+
+/// 创建分区的事件处理器，参数为分区的序号
+///
+/// 在分区的线程中调用，因此处理器本身无需实现`Send`
+pub type PartitionHandlerFactory = dyn Fn(usize) -> Box<dyn EventHandler> + Send + Sync;
+
+enum Task {
+    Event(OwnedEvent),
+    // 等待其它分区处理完此前的事件之后再处理，处理期间其它分区暂停
+    Exclusive(OwnedEvent, Arc<Barrier>),
+    // 暂停，直到单独处理的事件处理完毕
+    Pause(Arc<Barrier>),
+}
+
+struct Partition {
+    tasks: SyncSender<Task>,
+    thread: JoinHandle<()>,
+}
+
+/// 按key将事件分发到多个线程中并行处理的[`EventHandler`]，参见[模块文档](index.html)
+///
+/// AOF命令通过[`EventHandler::handle_command`]接收，解析只用于确定其中的key，分发的是master发送的原始命令，
+/// 由分区的线程重新解析，因此各分区的处理器看到的命令与master发送的完全相同。
+/// 直接通过`handle`传入已解析的命令时无法还原原始命令，将会panic(未解析的`Command::Other`除外)。
+/// 由于`Module`无法在线程之间传递，RDB中的Module将被忽略
+///
+/// 被drop时，等待所有分区处理完已分发的事件
+///
+/// [`EventHandler`]: ../trait.EventHandler.html
+/// [`EventHandler::handle_command`]: ../trait.EventHandler.html#method.handle_command
+pub struct PartitionedHandler {
+    partitions: Vec<Partition>,
+    depths: QueueDepths,
+}
+
+impl PartitionedHandler {
+    /// 启动`partitions`个分区，每个分区最多缓存`capacity`个尚未处理的事件，队列已满时分发将被阻塞
+    ///
+    /// 等待所有分区的处理器创建完毕之后返回，任一分区的`handler_factory`发生panic时返回错误
+    pub fn new(
+        partitions: usize, capacity: usize, handler_factory: Arc<PartitionHandlerFactory>,
+    ) -> Result<PartitionedHandler> {
+        let partitions = partitions.max(1);
+        let depths = QueueDepths(Arc::new((0..partitions).map(|_| AtomicUsize::new(0)).collect()));
+        let mut spawned = Vec::with_capacity(partitions);
+        let (ready, created) = mpsc::channel();
+        for index in 0..partitions {
+            let (tasks, receiver) = mpsc::sync_channel(capacity);
+            let factory = Arc::clone(&handler_factory);
+            let depths = depths.clone();
+            let ready = ready.clone();
+            let thread = thread::Builder::new()
+                .name(format!("partition-{}", index))
+                .spawn(move || {
+                    // 处理器创建失败时，分区无法参与屏障，由new返回错误
+                    let mut handler = match panic::catch_unwind(AssertUnwindSafe(|| factory(index))) {
+                        Ok(handler) => handler,
+                        Err(_) => {
+                            let _ = ready.send(Err(index));
+                            return;
+                        }
+                    };
+                    let _ = ready.send(Ok(index));
+                    drop(ready);
+                    let mut frame = CommandFrame::new();
+                    for task in receiver {
+                        match task {
+                            Task::Event(event) => {
+                                deliver(handler.as_mut(), event, &mut frame);
+                                depths.0[index].fetch_sub(1, Ordering::SeqCst);
+                            }
+                            Task::Exclusive(event, barrier) => {
+                                barrier.wait();
+                                deliver(handler.as_mut(), event, &mut frame);
+                                depths.0[index].fetch_sub(1, Ordering::SeqCst);
+                                barrier.wait();
+                            }
+                            Task::Pause(barrier) => {
+                                barrier.wait();
+                                barrier.wait();
+                            }
+                        }
+                    }
+                })?;
+            spawned.push(Partition { tasks, thread });
+        }
+        drop(ready);
+        // 出错时drop，等待已启动的分区退出
+        let handler = PartitionedHandler {
+            partitions: spawned,
+            depths,
+        };
+        for result in created.iter().take(partitions) {
+            if let Err(index) = result {
+                return Err(Error::other(format!("分区{}的事件处理器创建失败", index)));
+            }
+        }
+        Ok(handler)
+    }
+
+    /// 各分区中尚未处理完毕的事件数量，可在其它线程中查看
+    pub fn queue_depths(&self) -> QueueDepths {
+        self.depths.clone()
+    }
+
+    fn send(&self, index: usize, task: Task) {
+        // 暂停的标记不是事件，不计入队列深度
+        let counted = !matches!(task, Task::Pause(_));
+        if counted {
+            self.depths.0[index].fetch_add(1, Ordering::SeqCst);
+        }
+        if self.partitions[index].tasks.send(task).is_err() {
+            if counted {
+                self.depths.0[index].fetch_sub(1, Ordering::SeqCst);
+            }
+            error!("分区{}的线程已退出, 事件被丢弃", index);
+        }
+    }
+
+    fn dispatch(&self, partitions: &[usize], event: OwnedEvent) {
+        match partitions {
+            [index] => self.send(*index, Task::Event(event)),
+            _ => {
+                // 由第一个分区单独处理，不含key的事件由分区0处理
+                let target = partitions.first().copied().unwrap_or(0);
+                let barrier = Arc::new(Barrier::new(self.partitions.len()));
+                for index in (0..self.partitions.len()).filter(|index| *index != target) {
+                    self.send(index, Task::Pause(Arc::clone(&barrier)));
+                }
+                self.send(target, Task::Exclusive(event, barrier));
+            }
+        }
+    }
+
+    /// 事件中的key所属的分区，按分区序号排序
+    fn partitions_of(&self, event: &Event) -> Vec<usize> {
+        // 无法确定key时返回空，作为屏障处理
+        let mut partitions: Vec<usize> = event_keys(event)
+            .unwrap_or_default()
+            .iter()
+            .map(|key| slot_of(key) as usize % self.partitions.len())
+            .collect();
+        partitions.sort_unstable();
+        partitions.dedup();
+        partitions
+    }
+}
+
+impl EventHandler for PartitionedHandler {
+    fn handle(&mut self, event: Event) {
+        match event {
+            Event::RDB(ref obj) => {
+                let partitions = self.partitions_of(&event);
+                match OwnedObject::from_object(obj) {
+                    Some(owned) => self.dispatch(&partitions, OwnedEvent::RDB(owned)),
+                    None => warn!("Module无法分发到其它线程, 被忽略"),
+                }
+            }
+            // 未解析的命令无法确定key，作为屏障处理
+            Event::AOF(Command::Other(raw)) => self.dispatch(&[], OwnedEvent::AOF(raw)),
+            Event::AOF(_) => panic!("PartitionedHandler需要master发送的原始命令, AOF命令应通过handle_command传入"),
+        }
+    }
+
+    fn handle_command(&mut self, frame: &CommandFrame) {
+        let mut router = Router {
+            handler: self,
+            route: None,
+        };
+        frame.dispatch(&mut router);
+        let (route, cmd) = match (router.route, RawCommand::from_frame(frame)) {
+            (Some(route), Some(cmd)) => (route, cmd),
+            // 不产生事件的命令(如PING)无需分发
+            _ => return,
+        };
+        match route {
+            Route::All => {
+                for index in 0..self.partitions.len() {
+                    self.send(index, Task::Event(OwnedEvent::AOF(cmd.clone())));
+                }
+            }
+            Route::Partitions(partitions) => self.dispatch(&partitions, OwnedEvent::AOF(cmd)),
+        }
+    }
+}
+
+enum Route {
+    // 发送到每一个分区，如SELECT
+    All,
+    Partitions(Vec<usize>),
+}
+
+/// 解析命令以确定其中的key所属的分区，解析的结果只用于分区
+struct Router<'a> {
+    handler: &'a PartitionedHandler,
+    route: Option<Route>,
+}
+
+impl EventHandler for Router<'_> {
+    fn handle(&mut self, event: Event) {
+        self.route = Some(match event {
+            Event::AOF(Command::SELECT(_)) => Route::All,
+            _ => Route::Partitions(self.handler.partitions_of(&event)),
+        });
+    }
+}
+
+impl Drop for PartitionedHandler {
+    fn drop(&mut self) {
+        for partition in self.partitions.drain(..) {
+            drop(partition.tasks);
+            if partition.thread.join().is_err() {
+                error!("分区的线程异常退出");
+            }
+        }
+    }
+}
+
+/// 在分区的线程中处理事件，处理器panic时输出错误并继续处理之后的事件，避免其它分区一直等待
+///
+/// AOF命令以原始内容填充`frame`之后交由处理器的`handle_command`处理，与Listener中的处理方式相同
+fn deliver(handler: &mut dyn EventHandler, event: OwnedEvent, frame: &mut CommandFrame) {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| match &event {
+        OwnedEvent::RDB(obj) => handler.handle(Event::RDB(obj.as_object())),
+        OwnedEvent::AOF(cmd) => {
+            frame.fill(cmd);
+            handler.handle_command(frame);
+        }
+    }));
+    if result.is_err() {
+        error!("事件处理器panic: {:?}", event);
+    }
+}
+
+/// 各分区中尚未处理完毕的事件数量，包括正在处理的事件，不包括屏障期间用于暂停分区的标记
+#[derive(Debug, Clone)]
+pub struct QueueDepths(Arc<Vec<AtomicUsize>>);
+
+impl QueueDepths {
+    /// 按分区序号返回各分区的队列深度
+    pub fn get(&self) -> Vec<usize> {
+        self.0.iter().map(|depth| depth.load(Ordering::SeqCst)).collect()
+    }
+
+    /// 所有分区的队列深度之和
+    pub fn total(&self) -> usize {
+        self.0.iter().map(|depth| depth.load(Ordering::SeqCst)).sum()
+    }
+}
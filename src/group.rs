@@ -0,0 +1,367 @@
+/*!
+在同一个进程中监听多个Redis实例
+
+[`ListenerGroup`]为每个实例启动一个线程运行[`Listener`]，所有实例共用同一个心跳线程池，
+线程数可通过[`ListenerGroup::with_heartbeat_threads`]设置。实例出错(连接中断、解析失败甚至panic)之后，将在一段时间之后以此前的replication id及offset重新连接，
+不会影响其它实例。
+
+[`ListenerGroup`]: struct.ListenerGroup.html
+[`ListenerGroup::with_heartbeat_threads`]: struct.ListenerGroup.html#method.with_heartbeat_threads
+[`Listener`]: ../listener/struct.Listener.html
+*/
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io::{Error, ErrorKind, Result};
+use std::panic::{self, AssertUnwindSafe};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, sleep, JoinHandle};
+use std::time::{Duration, Instant};
+
+use log::{info, warn};
+use scheduled_thread_pool::ScheduledThreadPool;
+
+use crate::config::Config;
+use crate::listener::{Builder, ShutdownHandle};
+use crate::{EventHandler, RedisListener, Stopped};
+
+/// 创建事件处理器，参数为实例的名称
+///
+/// 在实例的线程中调用，每次(重新)连接时都会创建新的处理器
+pub type EventHandlerFactory = dyn Fn(&str) -> Rc<RefCell<dyn EventHandler>> + Send + Sync;
+
+/// 默认的心跳线程数
+const DEFAULT_HEARTBEAT_THREADS: usize = 4;
+
+/// 实例的运行状态
+#[derive(Debug, Clone, PartialEq)]
+pub enum InstanceState {
+    /// 正在同步
+    Running,
+    /// 出错之后等待重新连接
+    Restarting,
+    /// `is_aof`为false时，RDB处理完毕
+    Finished,
+    /// 已被中止
+    Stopped,
+}
+
+/// 实例的状态及同步进度
+#[derive(Debug, Clone)]
+pub struct InstanceStatus {
+    /// 实例的名称
+    pub name: String,
+    /// 运行状态
+    pub state: InstanceState,
+    /// Replication ID，在每次连接结束时更新
+    pub repl_id: String,
+    /// 最近一次确认的Replication Offset
+    pub repl_offset: i64,
+    /// 重新连接的次数
+    pub restarts: u32,
+    /// 最近一次出错的原因
+    pub last_error: Option<String>,
+}
+
+/// 监听多个Redis实例的事件
+pub struct ListenerGroup {
+    handler_factory: Arc<EventHandlerFactory>,
+    // 在启动第一个实例时创建，所有实例共用
+    thread_pool: Option<Arc<ScheduledThreadPool>>,
+    heartbeat_threads: usize,
+    restart_interval: Duration,
+    instances: HashMap<String, Instance>,
+}
+
+impl ListenerGroup {
+    /// 创建`ListenerGroup`，`handler_factory`用于为各个实例创建事件处理器
+    pub fn new(handler_factory: Arc<EventHandlerFactory>) -> ListenerGroup {
+        ListenerGroup {
+            handler_factory,
+            thread_pool: None,
+            heartbeat_threads: DEFAULT_HEARTBEAT_THREADS,
+            restart_interval: Duration::from_secs(5),
+            instances: HashMap::new(),
+        }
+    }
+
+    /// 设置组内心跳线程池的线程数，默认为4，须在启动第一个实例之前设置
+    ///
+    /// 心跳任务在ACK写入阻塞时(如未设置`write_timeout`)会一直占用线程，线程数应不少于可能同时阻塞的实例数，
+    /// 否则其它实例的心跳将被推迟
+    pub fn with_heartbeat_threads(&mut self, threads: usize) {
+        self.heartbeat_threads = threads.max(1);
+    }
+
+    /// 以指定的线程池代替组内创建的心跳线程池，之后启动的实例都将使用此线程池
+    pub fn with_thread_pool(&mut self, thread_pool: Arc<ScheduledThreadPool>) {
+        self.thread_pool = Some(thread_pool);
+    }
+
+    /// 设置实例出错之后，重新连接的间隔，默认为5秒
+    pub fn with_restart_interval(&mut self, interval: Duration) {
+        self.restart_interval = interval;
+    }
+
+    /// 开始监听一个实例，`name`在组内必须唯一
+    pub fn start(&mut self, name: &str, config: Config) -> Result<()> {
+        if let Some(instance) = self.instances.get(name) {
+            if instance.is_alive() {
+                return Err(Error::new(
+                    ErrorKind::AlreadyExists,
+                    format!("Instance {} is already running", name),
+                ));
+            }
+        }
+        let instance = self.spawn(name, config);
+        self.instances.insert(name.to_owned(), instance);
+        Ok(())
+    }
+
+    /// 中止一个实例，返回中止时的replication id及offset，实例不存在时返回None
+    pub fn stop(&mut self, name: &str) -> Option<Stopped> {
+        let instance = self.instances.get_mut(name)?;
+        instance.stop();
+        let status = instance.status();
+        Some(Stopped {
+            repl_id: status.repl_id,
+            repl_offset: status.repl_offset,
+        })
+    }
+
+    /// 中止一个实例，并以中止时的进度重新开始监听
+    pub fn restart(&mut self, name: &str) -> Result<()> {
+        let stopped = match self.stop(name) {
+            Some(stopped) => stopped,
+            None => return Err(Error::new(ErrorKind::NotFound, format!("Instance {} not found", name))),
+        };
+        let mut config = self.instances.remove(name).unwrap().config;
+        config.repl_id = stopped.repl_id;
+        config.repl_offset = stopped.repl_offset;
+        self.start(name, config)
+    }
+
+    /// 中止所有的实例，返回各个实例中止时的replication id及offset
+    pub fn stop_all(&mut self) -> HashMap<String, Stopped> {
+        // 先通知所有实例中止，再逐个等待，避免逐个等待时累积耗时
+        for instance in self.instances.values() {
+            instance.shutdown();
+        }
+        let names: Vec<String> = self.instances.keys().cloned().collect();
+        names
+            .into_iter()
+            .filter_map(|name| self.stop(&name).map(|stopped| (name, stopped)))
+            .collect()
+    }
+
+    /// 所有实例的状态
+    pub fn status(&self) -> Vec<InstanceStatus> {
+        let mut status: Vec<InstanceStatus> = self.instances.values().map(|instance| instance.status()).collect();
+        status.sort_by(|a, b| a.name.cmp(&b.name));
+        status
+    }
+
+    fn thread_pool(&mut self) -> Arc<ScheduledThreadPool> {
+        let threads = self.heartbeat_threads;
+        let thread_pool = self.thread_pool.get_or_insert_with(|| {
+            Arc::new(
+                ScheduledThreadPool::builder()
+                    .num_threads(threads)
+                    .thread_name_pattern("group-heartbeat-{}")
+                    .build(),
+            )
+        });
+        Arc::clone(thread_pool)
+    }
+
+    /// 实例所使用的心跳线程池
+    #[cfg(test)]
+    pub(crate) fn instance_thread_pool(&self, name: &str) -> Option<Arc<ScheduledThreadPool>> {
+        self.instances
+            .get(name)
+            .map(|instance| Arc::clone(&instance.thread_pool))
+    }
+
+    fn spawn(&mut self, name: &str, config: Config) -> Instance {
+        let thread_pool = self.thread_pool();
+        let running = Arc::new(AtomicBool::new(true));
+        let shared = Arc::new(Mutex::new(Shared {
+            status: InstanceStatus {
+                name: name.to_owned(),
+                state: InstanceState::Running,
+                repl_id: config.repl_id.clone(),
+                repl_offset: config.repl_offset,
+                restarts: 0,
+                last_error: None,
+            },
+            repl_offset: None,
+            shutdown_handle: None,
+        }));
+        let supervisor = Supervisor {
+            name: name.to_owned(),
+            config: config.clone(),
+            running: Arc::clone(&running),
+            shared: Arc::clone(&shared),
+            handler_factory: Arc::clone(&self.handler_factory),
+            thread_pool: Arc::clone(&thread_pool),
+            restart_interval: self.restart_interval,
+        };
+        let thread = thread::Builder::new()
+            .name(format!("listener-{}", name))
+            .spawn(move || supervisor.run())
+            .expect("spawn listener thread failed");
+        Instance {
+            config,
+            running,
+            shared,
+            thread_pool,
+            thread: Some(thread),
+        }
+    }
+}
+
+impl Drop for ListenerGroup {
+    fn drop(&mut self) {
+        self.stop_all();
+    }
+}
+
+/// 在实例的线程与`ListenerGroup`之间共享的数据
+struct Shared {
+    status: InstanceStatus,
+    repl_offset: Option<Arc<AtomicI64>>,
+    shutdown_handle: Option<ShutdownHandle>,
+}
+
+struct Instance {
+    config: Config,
+    running: Arc<AtomicBool>,
+    shared: Arc<Mutex<Shared>>,
+    // 仅用于在测试中确认各实例共用同一个线程池
+    #[cfg_attr(not(test), allow(dead_code))]
+    thread_pool: Arc<ScheduledThreadPool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Instance {
+    fn is_alive(&self) -> bool {
+        self.running.load(Ordering::SeqCst)
+            && self
+                .thread
+                .as_ref()
+                .map(|thread| !thread.is_finished())
+                .unwrap_or(false)
+    }
+
+    fn shutdown(&self) {
+        self.running.store(false, Ordering::SeqCst);
+        if let Some(handle) = &self.shared.lock().unwrap().shutdown_handle {
+            handle.shutdown();
+        }
+    }
+
+    fn stop(&mut self) {
+        self.shutdown();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+
+    fn status(&self) -> InstanceStatus {
+        let shared = self.shared.lock().unwrap();
+        let mut status = shared.status.clone();
+        if let Some(offset) = &shared.repl_offset {
+            status.repl_offset = offset.load(Ordering::SeqCst);
+        }
+        status
+    }
+}
+
+/// 在实例的线程中运行`Listener`，出错时重新连接
+struct Supervisor {
+    name: String,
+    config: Config,
+    running: Arc<AtomicBool>,
+    shared: Arc<Mutex<Shared>>,
+    handler_factory: Arc<EventHandlerFactory>,
+    thread_pool: Arc<ScheduledThreadPool>,
+    restart_interval: Duration,
+}
+
+impl Supervisor {
+    fn is_running(&self) -> bool {
+        self.running.load(Ordering::SeqCst)
+    }
+
+    fn run(mut self) {
+        let thread_pool = Arc::clone(&self.thread_pool);
+        loop {
+            let result = self.run_once(&thread_pool);
+            let mut shared = self.shared.lock().unwrap();
+            shared.status.repl_id = self.config.repl_id.clone();
+            shared.status.repl_offset = self.config.repl_offset;
+            shared.repl_offset = None;
+            shared.shutdown_handle = None;
+            match result {
+                Ok(()) if self.is_running() => {
+                    info!("实例 {} 处理完毕", &self.name);
+                    shared.status.state = InstanceState::Finished;
+                    return;
+                }
+                Err(err) if self.is_running() => {
+                    warn!(
+                        "实例 {} 出错: {}, {:?}之后重新连接",
+                        &self.name, err, self.restart_interval
+                    );
+                    shared.status.state = InstanceState::Restarting;
+                    shared.status.last_error = Some(err);
+                    shared.status.restarts += 1;
+                }
+                _ => {
+                    shared.status.state = InstanceState::Stopped;
+                    return;
+                }
+            }
+            drop(shared);
+            let restart_at = Instant::now() + self.restart_interval;
+            while Instant::now() < restart_at {
+                if !self.is_running() {
+                    self.shared.lock().unwrap().status.state = InstanceState::Stopped;
+                    return;
+                }
+                sleep(Duration::from_millis(100).min(self.restart_interval));
+            }
+        }
+    }
+
+    /// 运行一次`Listener`，结束后将进度记录在`config`中，panic也视为出错
+    fn run_once(&mut self, thread_pool: &Arc<ScheduledThreadPool>) -> std::result::Result<(), String> {
+        let mut builder = Builder::new();
+        builder.with_config(self.config.clone());
+        builder.with_control_flag(Arc::clone(&self.running));
+        builder.with_thread_pool(Arc::clone(thread_pool));
+        builder.with_event_handler((self.handler_factory)(&self.name));
+        let mut listener = builder.build();
+        {
+            let mut shared = self.shared.lock().unwrap();
+            shared.status.state = InstanceState::Running;
+            shared.repl_offset = Some(listener.repl_offset_counter());
+            shared.shutdown_handle = Some(listener.shutdown_handle());
+        }
+        let result = panic::catch_unwind(AssertUnwindSafe(|| listener.start()));
+        self.config.repl_id = listener.config.repl_id.clone();
+        self.config.repl_offset = listener.config.repl_offset;
+        match result {
+            Ok(Ok(_)) => Ok(()),
+            Ok(Err(err)) => Err(err.to_string()),
+            Err(cause) => Err(match cause.downcast_ref::<&str>() {
+                Some(msg) => format!("panic: {}", msg),
+                None => match cause.downcast_ref::<String>() {
+                    Some(msg) => format!("panic: {}", msg),
+                    None => String::from("panic"),
+                },
+            }),
+        }
+    }
+}
@@ -2,76 +2,250 @@
  处理redis的响应数据
 */
 
+use crate::config::Config;
 use crate::resp::*;
-use std::io::{BufReader, Error, ErrorKind, Read, Result, Write};
+use crate::Transport;
+use log::warn;
+use socket2::{SockRef, TcpKeepalive};
+use std::io::{BufRead, BufReader, Cursor, Error, ErrorKind, Read, Result, Write};
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::Receiver;
+use std::time::Duration;
 
-pub(crate) struct CountReader<'a> {
-    input: BufReader<&'a mut dyn Read>,
-    len: i64,
-    marked: bool,
+/// 与Redis之间的连接，读缓冲区在整个连接期间持续存在
+///
+/// 若每次读取都临时创建`BufReader`，预读到缓冲区中的数据会随之丢失，之后读取到的数据便会错位，
+/// 如紧跟在RDB之后的命令。写入不经过缓冲区，直接发送到底层连接
+pub(crate) struct Connection {
+    reader: BufReader<Box<dyn Transport>>,
 }
 
-impl Read for CountReader<'_> {
+impl Connection {
+    pub(crate) fn new(transport: Box<dyn Transport>) -> Connection {
+        Connection {
+            reader: BufReader::new(transport),
+        }
+    }
+
+    /// 拆分为底层连接，以及缓冲区中尚未读取的数据
+    pub(crate) fn into_parts(self) -> (Box<dyn Transport>, Vec<u8>) {
+        let buffered = self.reader.buffer().to_vec();
+        (self.reader.into_inner(), buffered)
+    }
+
+    pub(crate) fn try_clone(&self) -> Result<Box<dyn Transport>> {
+        self.reader.get_ref().try_clone()
+    }
+
+    pub(crate) fn shutdown(&self) -> Result<()> {
+        self.reader.get_ref().shutdown()
+    }
+
+    pub(crate) fn local_addr(&self) -> Option<SocketAddr> {
+        self.reader.get_ref().local_addr()
+    }
+}
+
+impl BufRead for Connection {
+    fn fill_buf(&mut self) -> Result<&[u8]> {
+        self.reader.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.reader.consume(amt)
+    }
+}
+
+impl Read for Connection {
     fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
-        let len = self.input.read(buf)?;
-        if self.marked {
-            self.len += len as i64;
-        };
-        Ok(len)
+        self.reader.read(buf)
     }
 
     fn read_exact(&mut self, buf: &mut [u8]) -> Result<()> {
-        self.input.read_exact(buf)?;
-        if self.marked {
-            self.len += buf.len() as i64;
-        };
-        Ok(())
+        self.reader.read_exact(buf)
     }
 }
 
-impl CountReader<'_> {
-    pub(crate) fn new(input: &mut dyn Read) -> CountReader {
-        CountReader {
-            input: BufReader::new(input),
-            len: 0,
-            marked: false,
-        }
+impl Write for Connection {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        self.reader.get_mut().write(buf)
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> Result<()> {
+        self.reader.get_mut().write_all(buf)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.reader.get_mut().flush()
     }
+}
+
+/// 按顺序读取由其它线程通过channel发送过来的数据
+///
+/// 发送端返回错误时，此错误将由`read`返回；发送端关闭之后视为EOF
+pub(crate) struct ChunkReader {
+    chunks: Receiver<Result<Vec<u8>>>,
+    chunk: Cursor<Vec<u8>>,
+}
 
-    pub(crate) fn mark(&mut self) {
-        self.marked = true;
+impl ChunkReader {
+    /// `first`将在channel中的数据之前返回
+    pub(crate) fn new(chunks: Receiver<Result<Vec<u8>>>, first: Vec<u8>) -> ChunkReader {
+        ChunkReader {
+            chunks,
+            chunk: Cursor::new(first),
+        }
     }
+}
 
-    pub(crate) fn reset(&mut self) -> Result<i64> {
-        if self.marked {
-            let len = self.len;
-            self.len = 0;
-            self.marked = false;
-            return Ok(len);
+impl Read for ChunkReader {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        loop {
+            let size = self.chunk.read(buf)?;
+            if size > 0 || buf.is_empty() {
+                return Ok(size);
+            }
+            match self.chunks.recv() {
+                Ok(Ok(chunk)) => self.chunk = Cursor::new(chunk),
+                Ok(Err(err)) => return Err(err),
+                Err(_) => return Ok(0),
+            }
         }
-        return Err(Error::new(ErrorKind::Other, "not marked"));
     }
 }
 
 pub(crate) fn send<T: Write>(output: &mut T, command: &[u8], args: &[&[u8]]) -> Result<()> {
-    let mut buf = vec![];
-    buf.write(&[STAR])?;
+    output.write_all(&encode(command, args))?;
+    output.flush()
+}
+
+/// 将命令编码为RESP格式
+pub(crate) fn encode(command: &[u8], args: &[&[u8]]) -> Vec<u8> {
+    let mut buf = vec![STAR];
     let args_len = args.len() + 1;
-    buf.write(&args_len.to_string().into_bytes())?;
-    buf.write(&[CR, LF, DOLLAR])?;
-    buf.write(&command.len().to_string().into_bytes())?;
-    buf.write(&[CR, LF])?;
-    buf.write(command)?;
-    buf.write(&[CR, LF])?;
+    buf.extend_from_slice(args_len.to_string().as_bytes());
+    buf.extend_from_slice(&[CR, LF, DOLLAR]);
+    buf.extend_from_slice(command.len().to_string().as_bytes());
+    buf.extend_from_slice(&[CR, LF]);
+    buf.extend_from_slice(command);
+    buf.extend_from_slice(&[CR, LF]);
     for arg in args {
-        buf.write(&[DOLLAR])?;
-        buf.write(&arg.len().to_string().into_bytes())?;
-        buf.write(&[CR, LF])?;
-        buf.write(arg)?;
-        buf.write(&[CR, LF])?;
+        buf.push(DOLLAR);
+        buf.extend_from_slice(arg.len().to_string().as_bytes());
+        buf.extend_from_slice(&[CR, LF]);
+        buf.extend_from_slice(arg);
+        buf.extend_from_slice(&[CR, LF]);
     }
-    output.write_all(&mut buf)?;
-    output.flush()
+    buf
+}
+
+/// 建立用于查询的TCP连接(如Sentinel、Cluster拓扑)，读写超时与连接超时相同，设置了密码时进行认证
+pub(crate) fn connect(host: &str, port: u16, username: &str, password: &str, timeout: Duration) -> Result<TcpStream> {
+    let mut conn = connect_tcp(host, port, Some(timeout))?;
+    conn.set_read_timeout(Some(timeout))?;
+    conn.set_write_timeout(Some(timeout))?;
+    auth(&mut conn, username, password)?;
+    Ok(conn)
+}
+
+/// 设置了密码时进行认证，认证失败时返回错误
+pub(crate) fn auth<T: Read + Write>(conn: &mut T, username: &str, password: &str) -> Result<()> {
+    if !password.is_empty() {
+        let mut args = Vec::with_capacity(2);
+        if !username.is_empty() {
+            args.push(username.as_bytes());
+        }
+        args.push(password.as_bytes());
+        send(conn, b"AUTH", &args)?;
+        if let Resp::Error(err) = conn.decode_resp()? {
+            return Err(Error::new(ErrorKind::PermissionDenied, err));
+        }
+    }
+    Ok(())
+}
+
+/// 依次尝试`host`解析出的每个地址，返回第一个建立成功的TCP连接
+///
+/// `timeout`为每个地址的连接超时，为None时使用操作系统的默认值
+pub(crate) fn connect_tcp(host: &str, port: u16, timeout: Option<Duration>) -> Result<TcpStream> {
+    let mut last_err = Error::new(
+        ErrorKind::NotFound,
+        format!("Unresolved address {}", format_addr(host, port)),
+    );
+    for addr in (host, port).to_socket_addrs()? {
+        let result = match timeout {
+            Some(timeout) => TcpStream::connect_timeout(&addr, timeout),
+            None => TcpStream::connect(addr),
+        };
+        match result {
+            Ok(conn) => return Ok(conn),
+            Err(err) => {
+                warn!("连接 {} 失败: {}", addr, err);
+                last_err = err;
+            }
+        }
+    }
+    Err(last_err)
+}
+
+/// 将host及port格式化为地址，IPv6地址以`[]`括起来
+pub(crate) fn format_addr(host: &str, port: u16) -> String {
+    if host.contains(':') {
+        format!("[{}]:{}", host, port)
+    } else {
+        format!("{}:{}", host, port)
+    }
+}
+
+/// 根据`Config`设置TCP keepalive及TCP_NODELAY
+pub(crate) fn set_tcp_options(socket: SockRef, config: &Config) -> Result<()> {
+    socket.set_nodelay(config.tcp_nodelay)?;
+    if let Some(idle) = config.tcp_keepalive {
+        socket.set_tcp_keepalive(&TcpKeepalive::new().with_time(idle))?;
+    }
+    Ok(())
+}
+
+/// 无盘复制时RDB之后EOF标记的长度
+pub(crate) const EOF_MARK_SIZE: usize = 40;
+
+/// 解析master发送RDB之前的`$<length>`或`$EOF:<mark>`，无盘复制时返回-1及EOF标记
+pub(crate) fn parse_rdb_length(reply: &str) -> Result<(i64, Option<Vec<u8>>)> {
+    if let Some(mark) = reply.strip_prefix("EOF:") {
+        if mark.len() != EOF_MARK_SIZE {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("Unexpected EOF mark: {}", reply),
+            ));
+        }
+        return Ok((-1, Some(mark.as_bytes().to_vec())));
+    }
+    match reply.parse::<i64>() {
+        Ok(length) => Ok((length, None)),
+        Err(_) => Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("Unexpected RDB length: {}", reply),
+        )),
+    }
+}
+
+/// 读取无盘复制RDB之后的EOF标记，与`$EOF:`中的标记不一致时返回错误
+///
+/// 不一致说明RDB解析器提前结束，或数据已损坏，此时继续读取命令将会错位
+pub(crate) fn verify_eof_mark(input: &mut dyn Read, mark: &[u8]) -> Result<()> {
+    let mut actual = [0; EOF_MARK_SIZE];
+    input.read_exact(&mut actual)?;
+    if actual[..] != *mark {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "Diskless RDB EOF mark mismatch, expected {}, got {}",
+                String::from_utf8_lossy(mark),
+                String::from_utf8_lossy(&actual)
+            ),
+        ));
+    }
+    Ok(())
 }
 
 // 跳过rdb的字节
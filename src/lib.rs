@@ -29,6 +29,7 @@
 *     let conf = Config {
 *         is_discard_rdb: false,            // 不跳过RDB
 *         is_aof: false,                    // 不处理AOF
+*         is_snapshot: false,               // 不使用快照模式
 *         host,
 *         port,
 *         username: String::new(),          // 用户名为空
@@ -40,7 +41,16 @@
 *         is_tls_enabled: false,            // 不启用TLS
 *         is_tls_insecure: false,           // 未启用TLS，设置为false即可
 *         identity: None,                   // 未启用TLS，设置为None即可
-*         identity_passwd: None             // 未启用TLS，设置为None即可
+*         identity_passwd: None,            // 未启用TLS，设置为None即可
+*         tls_ca_file: None,                // 未启用TLS，设置为None即可
+*         tls_cert_file: None,              // 未启用TLS，设置为None即可
+*         tls_key_file: None,               // 未启用TLS，设置为None即可
+*         tls_server_name: None,            // None，即使用host作为SNI
+*         connect_timeout: None,            // None，即使用操作系统默认的连接超时
+*         tcp_keepalive: None,              // None，即不开启TCP keepalive
+*         tcp_nodelay: false,               // 不开启TCP_NODELAY
+*         handshake: Default::default(),    // 默认的握手信息
+*         rdb_checksum: Default::default()  // 不校验RDB的校验和
 *     };
 *     let running = Arc::new(AtomicBool::new(true));
 *
@@ -62,25 +72,73 @@
 * [`Event`]: enum.Event.html
 */
 
-use std::io::{Read, Result};
+use std::io::{Read, Result, Write};
+use std::net::SocketAddr;
 
-use crate::cmd::Command;
-use crate::rdb::{Module, Object};
+use crate::cmd::{Command, RawCommand};
+use crate::rdb::{Module, Object, OwnedObject, Progress};
+use crate::resp::CommandFrame;
 
+#[cfg(feature = "async-tokio")]
+pub mod aio;
+pub mod cluster;
 pub mod cmd;
 pub mod config;
+mod crc64;
+pub mod dispatch;
+pub mod group;
 mod io;
 mod iter;
 pub mod listener;
 mod lzf;
+mod pipeline;
 pub mod rdb;
 pub mod resp;
+pub mod sentinel;
 mod tests;
+mod tls;
+mod transport;
 
 /// Redis事件监听器的定义，所有类型的监听器都实现此接口
 pub trait RedisListener {
     /// 开启事件监听
-    fn start(&mut self) -> Result<()>;
+    ///
+    /// 通过控制变量或`ShutdownHandle`中止时，返回[`Stopped`]，其中包含中止时的replication id及offset
+    ///
+    /// [`Stopped`]: struct.Stopped.html
+    fn start(&mut self) -> Result<Stopped>;
+}
+
+/// `RedisListener`正常停止时的状态
+///
+/// 下次启动时，将其中的`repl_id`及`repl_offset`设置到[`Config`]中即可尝试从中止的位置继续同步
+///
+/// [`Config`]: config/struct.Config.html
+#[derive(Debug, Clone)]
+pub struct Stopped {
+    /// 中止时的Replication ID
+    pub repl_id: String,
+    /// 中止时的Replication Offset
+    pub repl_offset: i64,
+}
+
+/// 与Redis之间的连接
+///
+/// 默认提供TCP及TLS两种实现，其它类型的连接(如SSH隧道，或者测试中使用的内存管道)实现此接口后，
+/// 可通过`listener::Builder::with_transport`传入
+pub trait Transport: Read + Write + Send {
+    /// 创建一个指向同一连接的新句柄，用于在其它线程中发送心跳及关闭连接
+    ///
+    /// 不支持时返回错误，此时心跳将在接收命令的间隙发送
+    fn try_clone(&self) -> Result<Box<dyn Transport>>;
+
+    /// 关闭连接，阻塞在此连接上的读写操作将立即返回
+    fn shutdown(&self) -> Result<()>;
+
+    /// 连接的本地地址，用于向Redis汇报replica的ip及端口
+    fn local_addr(&self) -> Option<SocketAddr> {
+        None
+    }
 }
 
 /// Redis RDB 解析器定义
@@ -109,9 +167,50 @@ pub enum Event<'a> {
     AOF(Command<'a>),
 }
 
+/// 拥有所有权的Redis事件，可在线程及异步任务之间传递
+///
+/// AOF事件为master发送的原始命令，参数与master发送的完全相同，未经解析与重新生成。
+/// 可通过[`RawCommand::dispatch`]在接收方(如[`PartitionedHandler`]的分区线程)中解析为[`Command`]，
+/// 因此接收方看到的命令与master发送的一致
+///
+/// [`RawCommand::dispatch`]: cmd/struct.RawCommand.html#method.dispatch
+/// [`Command`]: cmd/enum.Command.html
+/// [`PartitionedHandler`]: dispatch/struct.PartitionedHandler.html
+#[derive(Debug, Clone)]
+pub enum OwnedEvent {
+    /// RDB事件
+    RDB(OwnedObject),
+    /// AOF事件
+    AOF(RawCommand),
+}
+
 /// Redis事件处理器的定义，所有类型的处理器都必须实现此接口
 pub trait EventHandler {
     fn handle(&mut self, event: Event);
+
+    /// 处理master传播过来的一条命令，`frame`中为命令的原始内容
+    ///
+    /// 默认解析为[`Command`]之后交由`handle`处理。需要按原样保存或转发命令的处理器
+    /// (如[`PartitionedHandler`])可覆盖此方法
+    ///
+    /// [`Command`]: cmd/enum.Command.html
+    /// [`PartitionedHandler`]: dispatch/struct.PartitionedHandler.html
+    fn handle_command(&mut self, frame: &CommandFrame) {
+        frame.dispatch(&mut Forward(self));
+    }
+}
+
+/// 将事件转交给另一个处理器，使`EventHandler`的默认方法可以将`self`作为`&mut dyn EventHandler`使用
+struct Forward<'a, H: ?Sized>(&'a mut H);
+
+impl<H: EventHandler + ?Sized> EventHandler for Forward<'_, H> {
+    fn handle(&mut self, event: Event) {
+        self.0.handle(event);
+    }
+
+    fn handle_command(&mut self, frame: &CommandFrame) {
+        self.0.handle_command(frame);
+    }
 }
 
 /// 对于接收到的Redis事件不做任何处理
@@ -121,6 +220,13 @@ impl EventHandler for NoOpEventHandler {
     fn handle(&mut self, _: Event) {}
 }
 
+/// RDB解析进度的处理器，通过`listener::Builder::with_progress_handler`设置
+///
+/// 解析期间每秒最多调用一次，解析完毕时再调用一次，此时`Progress::finished`为true
+pub trait ProgressHandler {
+    fn handle(&mut self, progress: &Progress);
+}
+
 /// Module Parser
 pub trait ModuleParser {
     /// 解析Module的具体实现
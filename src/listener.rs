@@ -4,152 +4,97 @@
 [`RedisListener`]: trait.RedisListener.html
 */
 use std::cell::RefCell;
-use std::io::{BufRead, BufReader, Error, ErrorKind, Read, Result, Write};
-use std::net::TcpStream;
+use std::io::{Error, ErrorKind, Read, Result, Write};
+use std::mem;
+use std::net::SocketAddr;
 use std::ops::DerefMut;
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
 use std::rc::Rc;
 use std::result::Result::Ok;
 use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
-use std::sync::Arc;
-use std::thread::sleep;
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, sleep, JoinHandle};
 use std::time::{Duration, Instant};
 
 use log::{error, info, warn};
-use native_tls::{Identity, TlsConnector, TlsStream};
+use socket2::SockRef;
 
-use crate::config::Config;
-use crate::io::send;
-use crate::rdb::DefaultRDBParser;
-use crate::resp::{Resp, RespDecode, Type};
-use crate::{cmd, io, EventHandler, ModuleParser, NoOpEventHandler, RDBParser, RedisListener};
+use crate::cmd::RawCommand;
+use crate::config::{Config, RejectedOption};
+use crate::io::{send, Connection};
+use crate::pipeline::Pipeline;
+use crate::rdb::{DefaultRDBParser, OwnedObject};
+use crate::resp::{CommandFrame, Resp, RespDecode, Type};
+use crate::transport::BufferedTransport;
+use crate::{
+    io, tls, Event, EventHandler, ModuleParser, NoOpEventHandler, OwnedEvent, ProgressHandler, RDBParser,
+    RedisListener, Stopped, Transport,
+};
 use scheduled_thread_pool::{JobHandle, ScheduledThreadPool};
-use std::fs::File;
 
 /// 用于监听单个Redis实例的事件
 pub struct Listener {
     pub config: Config,
-    conn: Option<Stream>,
+    conn: Option<Connection>,
     rdb_parser: Rc<RefCell<dyn RDBParser>>,
     event_handler: Rc<RefCell<dyn EventHandler>>,
-    heartbeat_thread: HeartbeatWorker,
     running: Arc<AtomicBool>,
-    local_ip: Option<String>,
-    local_port: Option<u16>,
+    shutdown_handle: ShutdownHandle,
     thread_pool: Arc<ScheduledThreadPool>,
     repl_offset: Arc<AtomicI64>,
+    // 设置后，AOF命令不经解析直接交由此回调处理
+    command_handler: Option<Box<dyn FnMut(RawCommand)>>,
+    rejected_options: Vec<RejectedOption>,
+    rdb_pipeline: Option<Pipeline>,
 }
 
 impl Listener {
     /// 连接Redis，创建TCP连接
+    ///
+    /// 若通过`Builder::with_transport`指定了连接，则直接使用此连接
     fn connect(&mut self) -> Result<()> {
-        let addr = format!("{}:{}", &self.config.host, self.config.port);
-        let stream = TcpStream::connect(&addr)?;
-        stream
-            .set_read_timeout(self.config.read_timeout)
-            .expect("read timeout set failed");
-        stream
-            .set_write_timeout(self.config.write_timeout)
-            .expect("write timeout set failed");
-
-        let socket_addr = stream.local_addr().unwrap();
-        let local_ip = socket_addr.ip().to_string();
-        self.local_ip = Some(local_ip);
-
-        let local_port = socket_addr.port();
-        self.local_port = Some(local_port);
-
-        if self.config.is_tls_enabled {
-            let mut builder = TlsConnector::builder();
-            builder.danger_accept_invalid_hostnames(self.config.is_tls_insecure);
-            builder.danger_accept_invalid_certs(self.config.is_tls_insecure);
-
-            if let Some(id) = &self.config.identity {
-                let mut file = File::open(id)?;
-                let mut buff = Vec::new();
-                file.read_to_end(&mut buff)?;
-                let identity_passwd = match &self.config.identity_passwd {
-                    None => "",
-                    Some(passwd) => passwd.as_str(),
-                };
-                let identity = Identity::from_pkcs12(&buff, identity_passwd).expect("解析key失败");
-                builder.identity(identity);
-            }
-
-            let connector = builder.build().unwrap();
-            let tls_stream = connector
-                .connect(&self.config.host, stream)
-                .expect("TLS connect failed");
-            self.conn = Option::Some(Stream::Tls(tls_stream));
-        } else {
-            self.conn = Option::Some(Stream::Tcp(stream));
+        if self.conn.is_none() {
+            self.conn = Some(Connection::new(open_transport(&self.config)?));
+        }
+        let conn = self.conn.as_ref().unwrap();
+        match conn.try_clone() {
+            Ok(clone) => self.shutdown_handle.attach_transport(clone),
+            Err(err) => warn!("ShutdownHandle将无法中断阻塞中的读取: {}", err),
         }
-        info!("Connected to server {}", &addr);
         Ok(())
     }
 
     /// 如果有设置密码，将尝试使用此密码进行认证
     fn auth(&mut self) -> Result<()> {
-        if !self.config.password.is_empty() {
-            let mut args = Vec::with_capacity(2);
-            if !self.config.username.is_empty() {
-                args.push(self.config.username.as_bytes());
-            }
-            args.push(self.config.password.as_bytes());
-            let conn = self.conn.as_mut().unwrap();
-            let conn: &mut dyn Read = match conn {
-                Stream::Tcp(tcp_stream) => {
-                    send(tcp_stream, b"AUTH", &args)?;
-                    tcp_stream
-                }
-                Stream::Tls(tls_stream) => {
-                    send(tls_stream, b"AUTH", &args)?;
-                    tls_stream
-                }
-            };
-            conn.decode_resp()?;
-        }
-        Ok(())
+        auth(self.conn.as_mut().unwrap(), &self.config)
     }
 
     /// 发送replica相关信息到redis，此端口展现在`info replication`中
+    ///
+    /// master拒绝的选项不会中断握手，记录在`rejected_options`中
     fn send_replica_info(&mut self) -> Result<()> {
-        let port = self.local_port.unwrap().to_string();
-        let port = port.as_bytes();
-
-        let ip = self.local_ip.as_ref().unwrap();
-        let ip = ip.as_bytes();
-
         let conn = self.conn.as_mut().unwrap();
-        match conn {
-            Stream::Tcp(tcp_stream) => Listener::de_send_replica_info(&port, &ip, tcp_stream)?,
-            Stream::Tls(tls_stream) => Listener::de_send_replica_info(&port, &ip, tls_stream)?,
-        };
+        let commands = handshake_commands(&self.config, conn.local_addr());
+        self.rejected_options.clear();
+        for command in commands {
+            let line = command.join(" ");
+            info!("{}", line);
+            let args: Vec<&[u8]> = command[1..].iter().map(|arg| arg.as_bytes()).collect();
+            send(conn, command[0].as_bytes(), &args)?;
+            if let Some(error) = Listener::reply(conn)? {
+                self.rejected_options.push(RejectedOption { command: line, error });
+            }
+        }
+        if self.config.is_snapshot {
+            warn_snapshot_fallback(&self.rejected_options);
+        }
         Ok(())
     }
 
-    fn de_send_replica_info<T: Write + Read>(port: &&[u8], ip: &&[u8], tcp_stream: &mut T) -> Result<()> {
-        info!("PING");
-        send(tcp_stream, b"PING", &vec![])?;
-        Listener::reply(tcp_stream)?;
-
-        info!("REPLCONF listening-port {}", String::from_utf8_lossy(*port));
-        send(tcp_stream, b"REPLCONF", &[b"listening-port", port])?;
-        Listener::reply(tcp_stream)?;
-
-        info!("REPLCONF ip-address {}", String::from_utf8_lossy(*ip));
-        send(tcp_stream, b"REPLCONF", &[b"ip-address", ip])?;
-        Listener::reply(tcp_stream)?;
-
-        info!("REPLCONF capa eof");
-        send(tcp_stream, b"REPLCONF", &[b"capa", b"eof"])?;
-        Listener::reply(tcp_stream)?;
-
-        info!("REPLCONF capa psync2");
-        send(tcp_stream, b"REPLCONF", &[b"capa", b"psync2"])?;
-        Listener::reply(tcp_stream)
-    }
-
-    fn reply<T: Read>(tcp_stream: &mut T) -> Result<()> {
+    /// 读取握手命令的响应，master拒绝时返回错误信息，认证相关的错误将中断握手
+    fn reply<T: Read>(tcp_stream: &mut T) -> Result<Option<String>> {
         match tcp_stream.decode_resp()? {
             Resp::String(str) => info!("{}", str),
             Resp::Error(err) => {
@@ -160,18 +105,25 @@ impl Listener {
                 {
                     return Err(Error::new(ErrorKind::InvalidData, err));
                 }
+                return Ok(Some(err));
             }
             _ => panic!("Unexpected response type"),
         }
-        Ok(())
+        Ok(None)
     }
 
     /// 开启replication
     /// 默认使用PSYNC命令，若不支持PSYNC则尝试使用SYNC命令
     fn start_sync(&mut self) -> Result<Mode> {
-        let (next_step, mut length) = self.psync()?;
+        let prev_repl_id = self.config.repl_id.clone();
+        let prev_repl_offset = self.config.repl_offset;
+        let (next_step, mut length, eof_mark) = self.psync()?;
         match next_step {
             NextStep::FullSync | NextStep::ChangeMode => {
+                // FULLRESYNC返回的replication id及offset要等RDB接收完毕之后才生效，
+                // 否则中途中止或出错时，下次会以此offset继续同步，RDB中剩余的数据将会丢失
+                let repl_id = mem::replace(&mut self.config.repl_id, prev_repl_id);
+                let repl_offset = mem::replace(&mut self.config.repl_offset, prev_repl_offset);
                 let mode;
                 if let NextStep::ChangeMode = next_step {
                     info!("源Redis不支持PSYNC命令, 使用SYNC命令再次进行尝试");
@@ -185,24 +137,14 @@ impl Listener {
                 } else {
                     info!("Disk-less replication.");
                 }
-                let conn = self.conn.as_mut().unwrap();
-
-                let conn: &mut dyn Read = match conn {
-                    Stream::Tcp(tcp_stream) => tcp_stream,
-                    Stream::Tls(tls_stream) => tls_stream,
-                };
-                let mut reader = BufReader::new(conn);
-                reader.fill_buf()?;
-                if length != -1 && self.config.is_discard_rdb {
-                    info!("跳过RDB不进行处理");
-                    io::skip(&mut reader, length as isize)?;
-                } else {
-                    let mut event_handler = self.event_handler.borrow_mut();
-                    let mut rdb_parser = self.rdb_parser.borrow_mut();
-                    rdb_parser.parse(&mut reader, length, event_handler.deref_mut())?;
-                    if length == -1 {
-                        io::skip(&mut reader, 40)?;
-                    }
+                let mut conn = self.conn.take().unwrap();
+                let result = self.receive_rdb(&mut conn, length, eof_mark.as_deref());
+                self.conn = Some(conn);
+                result?;
+                if self.is_running() {
+                    self.config.repl_id = repl_id;
+                    self.config.repl_offset = repl_offset;
+                    self.repl_offset.store(repl_offset, Ordering::SeqCst);
                 }
                 Ok(mode)
             }
@@ -210,27 +152,149 @@ impl Listener {
                 info!("PSYNC进度恢复");
                 Ok(Mode::PSync)
             }
+            NextStep::DualChannel => {
+                self.dual_channel_sync()?;
+                Ok(Mode::PSync)
+            }
             NextStep::Wait => Ok(Mode::Wait),
         }
     }
 
-    fn psync(&mut self) -> Result<(NextStep, i64)> {
-        let offset = self.config.repl_offset.to_string();
-        let repl_offset = offset.as_bytes();
-        let repl_id = self.config.repl_id.as_bytes();
+    /// 接收并解析RDB，`length`为-1时表示无盘复制，此时RDB以`eof_mark`结尾
+    fn receive_rdb(&self, input: &mut Connection, length: i64, eof_mark: Option<&[u8]>) -> Result<()> {
+        if length != -1 && self.config.is_discard_rdb {
+            info!("跳过RDB不进行处理");
+            io::skip(input, length as isize)?;
+            return Ok(());
+        }
+        let eof_mark = match length {
+            -1 => Some(eof_mark.ok_or_else(|| Error::new(ErrorKind::InvalidData, "Missing EOF mark"))?),
+            _ => None,
+        };
+        let mut event_handler = self.event_handler.borrow_mut();
+        if let Some(pipeline) = &self.rdb_pipeline {
+            return pipeline.receive_rdb(input, length, eof_mark, &self.running, event_handler.deref_mut());
+        }
+        let mut rdb_parser = self.rdb_parser.borrow_mut();
+        rdb_parser.parse(input, length, event_handler.deref_mut())?;
+        if let Some(eof_mark) = eof_mark {
+            io::verify_eof_mark(input, eof_mark)?;
+        }
+        Ok(())
+    }
 
-        let conn = self.conn.as_mut().unwrap();
-        let conn: &mut dyn Read = match conn {
-            Stream::Tcp(tcp_stream) => {
-                send(tcp_stream, b"PSYNC", &[repl_id, repl_offset])?;
+    /// dual-channel复制，master回复`+DUALCHANNELSYNC`之后进行
+    ///
+    /// RDB通过另一条连接接收，主连接则以RDB对应的offset继续同步，接收RDB期间master传播的命令由后台线程缓存在本地，
+    /// RDB处理完毕之后再按顺序处理。master因此无需在全量同步期间为此replica缓存命令
+    fn dual_channel_sync(&mut self) -> Result<()> {
+        info!("Dual-channel replication, 建立RDB连接");
+        let mut rdb_conn = Connection::new(open_transport(&self.config)?);
+        match rdb_conn.try_clone() {
+            Ok(clone) => self.shutdown_handle.attach_rdb_channel(clone),
+            Err(err) => warn!("ShutdownHandle将无法中断RDB的读取: {}", err),
+        }
+        let result = self.receive_rdb_channel(&mut rdb_conn);
+        self.shutdown_handle.detach_rdb_channel();
+        if let Err(err) = rdb_conn.shutdown() {
+            warn!("关闭RDB连接失败: {}", err);
+        }
+        result
+    }
 
-                tcp_stream
+    fn receive_rdb_channel(&mut self, rdb_conn: &mut Connection) -> Result<()> {
+        auth(rdb_conn, &self.config)?;
+        // 与Valkey的replica相同: REPLCONF capa eof rdb-only 1 rdb-channel 1 listening-port <port>，
+        // 缺少rdb-channel时master将回复+FULLRESYNC而不是$ENDOFF
+        let local_addr = self.conn.as_ref().and_then(|conn| conn.local_addr());
+        let port = self
+            .config
+            .handshake
+            .announce_port
+            .or_else(|| local_addr.map(|addr| addr.port()))
+            .map(|port| port.to_string());
+        let mut args: Vec<&[u8]> = vec![b"capa", b"eof", b"rdb-only", b"1", b"rdb-channel", b"1"];
+        if let Some(port) = &port {
+            args.extend_from_slice(&[b"listening-port", port.as_bytes()]);
+        }
+        send(rdb_conn, b"REPLCONF", &args)?;
+        if let Some(err) = Listener::reply(rdb_conn)? {
+            return Err(Error::new(ErrorKind::InvalidData, err));
+        }
+        send(rdb_conn, b"SYNC", &[])?;
+
+        // $ENDOFF:<offset> <replication id> <db> <client id>，offset为RDB对应的replication offset
+        info!("等待Redis dump完成...");
+        let end_offset = match rdb_conn.decode_type()? {
+            Type::BulkString => rdb_conn.decode_string()?,
+            _ => return Err(Error::new(ErrorKind::InvalidData, "Expect $ENDOFF response")),
+        };
+        info!("{}", end_offset);
+        let mut iter = end_offset
+            .strip_prefix("ENDOFF:")
+            .unwrap_or_default()
+            .split_whitespace();
+        let (repl_offset, repl_id, client_id) = match (iter.next(), iter.next(), iter.nth(1)) {
+            (Some(offset), Some(repl_id), Some(client_id)) => match offset.parse::<i64>() {
+                Ok(offset) => (offset, repl_id.to_owned(), client_id.to_owned()),
+                Err(_) => return Err(Error::new(ErrorKind::InvalidData, end_offset)),
+            },
+            _ => return Err(Error::new(ErrorKind::InvalidData, end_offset)),
+        };
+
+        // 主连接从RDB之后的第一个字节开始继续同步
+        let conn = self.conn.as_mut().unwrap();
+        send(conn, b"REPLCONF", &[b"set-rdb-client-id", client_id.as_bytes()])?;
+        if let Some(err) = Listener::reply(conn)? {
+            return Err(Error::new(ErrorKind::InvalidData, err));
+        }
+        let psync_offset = (repl_offset + 1).to_string();
+        send(conn, b"PSYNC", &[repl_id.as_bytes(), psync_offset.as_bytes()])?;
+        match conn.decode_resp()? {
+            Resp::String(resp) if resp.starts_with("CONTINUE") => info!("{}", resp),
+            resp => {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("Unexpected response: {:?}", resp),
+                ))
+            }
+        }
+        let conn = self.conn.take().unwrap();
+        self.conn = Some(match conn.try_clone() {
+            Ok(reader) => {
+                // 已读入缓冲区的命令同样需要交给后台线程，否则将被跳过
+                let (conn, buffered) = conn.into_parts();
+                Connection::new(Box::new(BufferedTransport::start(conn, reader, buffered)?))
             }
-            Stream::Tls(tls_stream) => {
-                send(tls_stream, b"PSYNC", &[repl_id, repl_offset])?;
-                tls_stream
+            Err(err) => {
+                // 无法在后台读取时，命令将缓存在master中
+                warn!("{}, 接收RDB期间将不会读取主连接", err);
+                conn
             }
+        });
+
+        let (length, eof_mark) = match rdb_conn.decode_type()? {
+            Type::BulkString => io::parse_rdb_length(&rdb_conn.decode_string()?)?,
+            _ => return Err(Error::new(ErrorKind::InvalidData, "Expect BulkString response")),
         };
+        if length != -1 {
+            info!("Full Sync, size: {}bytes", length);
+        } else {
+            info!("Disk-less replication.");
+        }
+        self.receive_rdb(rdb_conn, length, eof_mark.as_deref())?;
+        if self.is_running() {
+            self.config.repl_id = repl_id;
+            self.config.repl_offset = repl_offset;
+            self.repl_offset.store(repl_offset, Ordering::SeqCst);
+        }
+        Ok(())
+    }
+
+    fn psync(&mut self) -> Result<(NextStep, i64, Option<Vec<u8>>)> {
+        let (repl_id, repl_offset) = psync_args(&self.config);
+        let conn = self.conn.as_mut().unwrap();
+        send(conn, b"PSYNC", &[repl_id.as_bytes(), repl_offset.as_bytes()])?;
 
         match conn.decode_resp() {
             Ok(response) => {
@@ -250,13 +314,8 @@ impl Listener {
                         }
                         info!("等待Redis dump完成...");
                         if let Type::BulkString = conn.decode_type()? {
-                            let reply = conn.decode_string()?;
-                            if reply.starts_with("EOF") {
-                                return Ok((NextStep::FullSync, -1));
-                            } else {
-                                let length = reply.parse::<i64>().unwrap();
-                                return Ok((NextStep::FullSync, length));
-                            }
+                            let (length, eof_mark) = io::parse_rdb_length(&conn.decode_string()?)?;
+                            return Ok((NextStep::FullSync, length, eof_mark));
                         } else {
                             panic!("Expect BulkString response");
                         }
@@ -267,18 +326,20 @@ impl Listener {
                                 self.config.repl_id = repl_id.to_owned();
                             }
                         }
-                        return Ok((NextStep::PartialResync, -1));
+                        return Ok((NextStep::PartialResync, -1, None));
+                    } else if resp.starts_with("DUALCHANNELSYNC") {
+                        return Ok((NextStep::DualChannel, -1, None));
                     } else if resp.starts_with("NOMASTERLINK") {
-                        return Ok((NextStep::Wait, -1));
+                        return Ok((NextStep::Wait, -1, None));
                     } else if resp.starts_with("LOADING") {
-                        return Ok((NextStep::Wait, -1));
+                        return Ok((NextStep::Wait, -1, None));
                     }
                 }
                 panic!("Unexpected Response: {:?}", response);
             }
             Err(error) => {
                 if error.to_string().eq("ERR unknown command 'PSYNC'") {
-                    return Ok((NextStep::ChangeMode, -1));
+                    return Ok((NextStep::ChangeMode, -1, None));
                 } else {
                     return Err(error);
                 }
@@ -288,16 +349,7 @@ impl Listener {
 
     fn sync(&mut self) -> Result<i64> {
         let conn = self.conn.as_mut().unwrap();
-        let conn: &mut dyn Read = match conn {
-            Stream::Tcp(tcp_stream) => {
-                send(tcp_stream, b"SYNC", &vec![])?;
-                tcp_stream
-            }
-            Stream::Tls(tls_stream) => {
-                send(tls_stream, b"SYNC", &vec![])?;
-                tls_stream
-            }
-        };
+        send(conn, b"SYNC", &vec![])?;
         if let Type::BulkString = conn.decode_type()? {
             if let Resp::Int(length) = conn.decode_int()? {
                 return Ok(length);
@@ -310,119 +362,199 @@ impl Listener {
     }
 
     /// 开启心跳
-    fn start_heartbeat(&mut self, mode: &Mode) {
+    ///
+    /// 返回是否已在后台发送心跳，若连接不支持复制，则由`receive_aof`在接收命令的间隙发送
+    fn start_heartbeat(&mut self, mode: &Mode) -> bool {
         if !self.is_running() {
-            return;
+            return false;
         }
         if let Mode::Sync = mode {
-            return;
-        }
-        if self.config.is_tls_enabled {
-            return;
+            return false;
         }
         let conn = self.conn.as_ref().unwrap();
-        let conn = match conn {
-            Stream::Tcp(tcp_stream) => tcp_stream,
-            Stream::Tls(_) => panic!("Expect TcpStream"),
+        let mut conn_clone = match conn.try_clone() {
+            Ok(conn_clone) => conn_clone,
+            Err(err) => {
+                info!("{}, heartbeat will be sent between commands", err);
+                return false;
+            }
         };
-        let mut conn_clone = conn.try_clone().unwrap();
         info!("Start heartbeat");
         let repl_offset = Arc::clone(&self.repl_offset);
         let handle =
             self.thread_pool
                 .execute_with_fixed_delay(Duration::from_secs(0), Duration::from_secs(1), move || {
-                    let offset_str = repl_offset.load(Ordering::Relaxed).to_string();
-                    let offset_bytes = offset_str.as_bytes();
-                    if let Err(error) = send(&mut conn_clone, b"REPLCONF", &[b"ACK", offset_bytes]) {
+                    let offset = repl_offset.load(Ordering::Relaxed);
+                    if let Err(error) = Listener::send_ack(&mut conn_clone, offset) {
                         error!("heartbeat error: {}", error);
                     }
                 });
 
-        self.heartbeat_thread = HeartbeatWorker { handle: Some(handle) };
+        self.shutdown_handle.attach_heartbeat(handle);
+        true
     }
 
-    fn receive_aof(&mut self, mode: &Mode) -> Result<()> {
+    fn receive_aof(&mut self, mode: &Mode, heartbeat_started: bool) -> Result<()> {
         let mut handler = self.event_handler.as_ref().borrow_mut();
 
-        let __conn = self.conn.as_mut().unwrap();
-        match __conn {
-            Stream::Tcp(tcp_stream) => {
-                let mut reader = io::CountReader::new(tcp_stream);
-
-                while self.running.load(Ordering::Relaxed) {
-                    reader.mark();
-                    if let Resp::Array(array) = reader.decode_resp()? {
-                        let size = reader.reset()?;
-                        let mut vec = Vec::with_capacity(array.len());
-                        for x in array {
-                            if let Resp::BulkBytes(bytes) = x {
-                                vec.push(bytes);
-                            } else {
-                                panic!("Expected BulkString response");
-                            }
-                        }
-                        cmd::parse(vec, handler.deref_mut());
-                        if let Mode::PSync = mode {
-                            self.config.repl_offset += size;
-                            self.repl_offset.store(self.config.repl_offset, Ordering::SeqCst);
-                        }
-                    } else {
-                        panic!("Expected array response");
+        let conn = self.conn.as_mut().unwrap();
+        let mut frame = CommandFrame::new();
+        let inline_heartbeat = !heartbeat_started && matches!(mode, Mode::PSync);
+        let mut timer = Instant::now();
+        let one_sec = Duration::from_secs(1);
+
+        while self.running.load(Ordering::Relaxed) {
+            let size = frame.read_from(conn)?;
+            if is_getack(frame.args()) {
+                // ACK中的offset不包含GETACK命令本身，与Redis replica的行为保持一致
+                Listener::send_ack(conn, self.config.repl_offset)?;
+                timer = Instant::now();
+            }
+            match &mut self.command_handler {
+                Some(command_handler) => {
+                    if let Some(cmd) = RawCommand::from_frame(&frame) {
+                        command_handler(cmd);
                     }
                 }
+                None => handler.handle_command(&frame),
+            }
+            if let Mode::PSync = mode {
+                self.config.repl_offset += size as i64;
+                self.repl_offset.store(self.config.repl_offset, Ordering::SeqCst);
             }
-            Stream::Tls(tls_stream) => {
-                let mut timer = Instant::now();
-                let one_sec = Duration::from_secs(1);
-
-                while self.running.load(Ordering::Relaxed) {
-                    {
-                        let mut reader = io::CountReader::new(tls_stream);
-                        reader.mark();
-                        if let Resp::Array(array) = reader.decode_resp()? {
-                            let size = reader.reset()?;
-                            let mut vec = Vec::with_capacity(array.len());
-                            for x in array {
-                                if let Resp::BulkBytes(bytes) = x {
-                                    vec.push(bytes);
-                                } else {
-                                    panic!("Expected BulkString response");
-                                }
-                            }
-                            cmd::parse(vec, handler.deref_mut());
-                            self.config.repl_offset += size;
-                        } else {
-                            panic!("Expected array response");
-                        }
-                    }
 
-                    let elapsed = timer.elapsed();
-                    if elapsed.ge(&one_sec) {
-                        let offset_str = self.config.repl_offset.to_string();
-                        let offset_bytes = offset_str.as_bytes();
-                        if let Err(error) = send(tls_stream, b"REPLCONF", &[b"ACK", offset_bytes]) {
-                            error!("heartbeat error: {}", error);
-                            break;
-                        }
-                        timer = Instant::now();
-                    }
+            if inline_heartbeat && timer.elapsed().ge(&one_sec) {
+                if let Err(error) = Listener::send_ack(conn, self.config.repl_offset) {
+                    error!("heartbeat error: {}", error);
+                    break;
                 }
+                timer = Instant::now();
             }
-        };
+        }
         Ok(())
     }
 
+    /// 向master汇报当前的replication offset
+    fn send_ack<T: Write>(output: &mut T, offset: i64) -> Result<()> {
+        let offset = offset.to_string();
+        send(output, b"REPLCONF", &[b"ACK", offset.as_bytes()])
+    }
+
     /// 获取当前运行的状态，若为false，程序将有序退出
     fn is_running(&self) -> bool {
         self.running.load(Ordering::Relaxed)
     }
-}
 
-impl RedisListener for Listener {
-    /// 程序运行的整体逻辑都在这个方法里面实现
+    /// 获取用于中止此`Listener`的[`ShutdownHandle`]，可在其它线程中使用
     ///
-    /// 具体的细节体现在各个方法内
-    fn start(&mut self) -> Result<()> {
+    /// [`ShutdownHandle`]: struct.ShutdownHandle.html
+    pub fn shutdown_handle(&self) -> ShutdownHandle {
+        self.shutdown_handle.clone()
+    }
+
+    /// 最近一次握手中被master拒绝的选项，如旧版本Redis不支持的`REPLCONF`选项
+    pub fn rejected_options(&self) -> &[RejectedOption] {
+        &self.rejected_options
+    }
+
+    /// 实时的replication offset，用于在其它线程中查看同步进度
+    pub(crate) fn repl_offset_counter(&self) -> Arc<AtomicI64> {
+        Arc::clone(&self.repl_offset)
+    }
+
+    /// 在后台线程中运行此`Listener`，返回以拉取方式获取事件的[`Events`]
+    ///
+    /// 事件缓存在容量为`capacity`的有界队列中，队列已满时后台线程将暂停读取，不会无限制地占用内存。
+    /// RDB数据以[`OwnedObject`]的形式给出，AOF命令则不经解析，以[`RawCommand`]的形式给出，可通过`RawCommand::dispatch`解析。
+    ///
+    /// 由于`Rc`无法跨线程传递，通过`Builder`设置的以下内容不会被使用:
+    /// - `with_rdb_parser`设置的`RDBParser`，RDB总是由默认的解析器解析
+    /// - `with_event_handler`设置的`EventHandler`，事件全部通过`Events`给出
+    /// - `with_module_parser`设置的`ModuleParser`，RDB中的Module将被忽略
+    /// - `with_progress_handler`设置的`ProgressHandler`，不会汇报RDB的解析进度
+    ///
+    /// `with_rdb_pipeline`设置的流水线解析仍然有效，`Config`、控制变量、心跳线程池及自定义的连接也同样会被使用
+    ///
+    /// [`Events`]: struct.Events.html
+    /// [`OwnedObject`]: ../rdb/enum.OwnedObject.html
+    /// [`RawCommand`]: ../cmd/struct.RawCommand.html
+    pub fn into_events(mut self, capacity: usize) -> Events {
+        let config = self.config.clone();
+        let rdb_pipeline = self
+            .rdb_pipeline
+            .as_ref()
+            .map(|pipeline| (pipeline.buffer_size, pipeline.checksum));
+        let conn = self.conn.take();
+        let running = Arc::clone(&self.running);
+        let shutdown_handle = self.shutdown_handle.clone();
+        let thread_pool = Arc::clone(&self.thread_pool);
+        let repl_offset = Arc::clone(&self.repl_offset);
+        // 在后台线程开始之前释放，避免其Drop取消后台线程中的心跳
+        drop(self);
+
+        let (sender, receiver) = mpsc::sync_channel(capacity);
+        let handle = shutdown_handle.clone();
+        let thread = thread::Builder::new()
+            .name("listener-events".to_string())
+            .spawn(move || {
+                let command_sender = ChannelHandler {
+                    events: sender.clone(),
+                    shutdown_handle: handle.clone(),
+                };
+                let rdb_parser = DefaultRDBParser {
+                    running: Arc::clone(&running),
+                    module_parser: None,
+                    checksum: config.rdb_checksum,
+                    progress_handler: None,
+                };
+                let mut listener = Listener {
+                    config,
+                    conn,
+                    rdb_parser: Rc::new(RefCell::new(rdb_parser)),
+                    event_handler: Rc::new(RefCell::new(ChannelHandler {
+                        events: sender.clone(),
+                        shutdown_handle: handle.clone(),
+                    })),
+                    running,
+                    shutdown_handle: handle,
+                    thread_pool,
+                    repl_offset,
+                    command_handler: Some(Box::new(move |cmd: RawCommand| {
+                        if cmd.name != "PING" && cmd.name != "REPLCONF" {
+                            command_sender.send(OwnedEvent::AOF(cmd));
+                        }
+                    })),
+                    rejected_options: Vec::new(),
+                    rdb_pipeline: rdb_pipeline.map(|(buffer_size, checksum)| Pipeline {
+                        buffer_size,
+                        checksum,
+                        progress_handler: None,
+                    }),
+                };
+                match listener.start() {
+                    Ok(stopped) => stopped,
+                    Err(err) => {
+                        let _ = sender.send(Err(err));
+                        listener.stopped()
+                    }
+                }
+            })
+            .expect("spawn listener thread failed");
+        Events {
+            receiver: Some(receiver),
+            shutdown_handle,
+            thread: Some(thread),
+        }
+    }
+
+    fn stopped(&self) -> Stopped {
+        Stopped {
+            repl_id: self.config.repl_id.clone(),
+            repl_offset: self.config.repl_offset,
+        }
+    }
+
+    fn run(&mut self) -> Result<()> {
         self.connect()?;
         self.auth()?;
         self.send_replica_info()?;
@@ -440,32 +572,338 @@ impl RedisListener for Listener {
                 _ => break,
             }
         }
-        if !self.config.is_aof {
-            Ok(())
-        } else {
-            self.start_heartbeat(&mode);
-            self.receive_aof(&mode)?;
-            Ok(())
+        if self.config.is_aof {
+            let heartbeat_started = self.start_heartbeat(&mode);
+            self.receive_aof(&mode, heartbeat_started)?;
+        } else if self.config.is_snapshot {
+            info!("快照已接收完毕, 关闭连接");
+            if let Some(Err(err)) = self.conn.take().map(|conn| conn.shutdown()) {
+                warn!("关闭连接失败: {}", err);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl RedisListener for Listener {
+    /// 程序运行的整体逻辑都在这个方法里面实现
+    ///
+    /// 具体的细节体现在各个方法内
+    fn start(&mut self) -> Result<Stopped> {
+        let result = self.run();
+        self.shutdown_handle.cancel_heartbeat();
+        match result {
+            Err(err) if self.is_running() => Err(err),
+            Err(err) => {
+                // 连接被ShutdownHandle关闭，阻塞中的读取会以错误返回，此时视为正常停止
+                info!("Listener stopped: {}", err);
+                Ok(self.stopped())
+            }
+            Ok(()) => Ok(self.stopped()),
         }
     }
 }
 
 impl Drop for Listener {
     fn drop(&mut self) {
-        if let Some(handle) = &self.heartbeat_thread.handle {
+        self.shutdown_handle.cancel_heartbeat();
+    }
+}
+
+/// 用于在其它线程中止[`Listener`]
+///
+/// 仅设置控制变量的话，`Listener`要等到下一条命令到达才能发现，若master一直没有写入，`Listener`将一直阻塞在读取上。
+/// `ShutdownHandle`在设置控制变量的同时会关闭与Redis的连接并取消心跳，使`Listener::start`立即返回。
+///
+/// [`Listener`]: struct.Listener.html
+#[derive(Clone)]
+pub struct ShutdownHandle {
+    running: Arc<AtomicBool>,
+    inner: Arc<Mutex<ShutdownInner>>,
+}
+
+struct ShutdownInner {
+    transport: Option<Box<dyn Transport>>,
+    // dual-channel复制时接收RDB的连接
+    rdb_channel: Option<Box<dyn Transport>>,
+    heartbeat: Option<JobHandle>,
+}
+
+impl ShutdownHandle {
+    fn new(running: Arc<AtomicBool>) -> ShutdownHandle {
+        ShutdownHandle {
+            running,
+            inner: Arc::new(Mutex::new(ShutdownInner {
+                transport: None,
+                rdb_channel: None,
+                heartbeat: None,
+            })),
+        }
+    }
+
+    /// 中止`Listener`，可重复调用
+    pub fn shutdown(&self) {
+        self.running.store(false, Ordering::SeqCst);
+        let mut inner = self.inner.lock().unwrap();
+        if let Some(handle) = inner.heartbeat.take() {
             info!("Cancel heartbeat");
             handle.cancel();
         }
+        for transport in inner.transport.iter().chain(inner.rdb_channel.iter()) {
+            if let Err(err) = transport.shutdown() {
+                warn!("shutdown connection failed: {}", err);
+            }
+        }
+    }
+
+    fn attach_transport(&self, transport: Box<dyn Transport>) {
+        let mut inner = self.inner.lock().unwrap();
+        // 在连接建立之前就已经调用了shutdown
+        if !self.running.load(Ordering::SeqCst) {
+            let _ = transport.shutdown();
+        }
+        inner.transport = Some(transport);
+    }
+
+    fn attach_rdb_channel(&self, transport: Box<dyn Transport>) {
+        let mut inner = self.inner.lock().unwrap();
+        if !self.running.load(Ordering::SeqCst) {
+            let _ = transport.shutdown();
+        }
+        inner.rdb_channel = Some(transport);
+    }
+
+    fn detach_rdb_channel(&self) {
+        self.inner.lock().unwrap().rdb_channel = None;
+    }
+
+    fn attach_heartbeat(&self, handle: JobHandle) {
+        let mut inner = self.inner.lock().unwrap();
+        if !self.running.load(Ordering::SeqCst) {
+            handle.cancel();
+        }
+        inner.heartbeat = Some(handle);
+    }
+
+    fn cancel_heartbeat(&self) {
+        if let Some(handle) = self.inner.lock().unwrap().heartbeat.take() {
+            info!("Cancel heartbeat");
+            handle.cancel();
+        }
+    }
+}
+
+/// 以拉取方式获取[`Listener`]的事件，由[`Listener::into_events`]创建
+///
+/// 同步结束(`is_aof`为false时RDB处理完毕，或出错)之后迭代结束，出错时最后一个元素为错误。
+/// 丢弃`Events`时后台的`Listener`将随之中止，需要获取中止时的进度时，调用[`stop`]
+///
+/// [`Listener`]: struct.Listener.html
+/// [`Listener::into_events`]: struct.Listener.html#method.into_events
+/// [`stop`]: struct.Events.html#method.stop
+pub struct Events {
+    receiver: Option<Receiver<Result<OwnedEvent>>>,
+    shutdown_handle: ShutdownHandle,
+    thread: Option<JoinHandle<Stopped>>,
+}
+
+impl Events {
+    /// 获取用于中止后台`Listener`的`ShutdownHandle`
+    pub fn shutdown_handle(&self) -> ShutdownHandle {
+        self.shutdown_handle.clone()
+    }
+
+    /// 中止后台的`Listener`，返回中止时的replication id及offset
+    ///
+    /// 同步因出错而结束时，返回的是出错之前已确认的进度，可用于重新连接
+    pub fn stop(mut self) -> Result<Stopped> {
+        self.shutdown_handle.shutdown();
+        // 先释放队列，使阻塞在发送上的后台线程退出
+        self.receiver.take();
+        match self.thread.take().unwrap().join() {
+            Ok(stopped) => Ok(stopped),
+            Err(_) => Err(Error::new(ErrorKind::Interrupted, "listener thread panicked")),
+        }
+    }
+}
+
+impl Iterator for Events {
+    type Item = Result<OwnedEvent>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.receiver.as_ref()?.recv().ok()
+    }
+}
+
+impl Drop for Events {
+    fn drop(&mut self) {
+        self.shutdown_handle.shutdown();
+    }
+}
+
+/// 将事件转换为`OwnedEvent`放入队列，队列已满时阻塞，`Events`被丢弃之后中止`Listener`
+struct ChannelHandler {
+    events: SyncSender<Result<OwnedEvent>>,
+    shutdown_handle: ShutdownHandle,
+}
+
+impl ChannelHandler {
+    fn send(&self, event: OwnedEvent) {
+        if self.events.send(Ok(event)).is_err() {
+            self.shutdown_handle.shutdown();
+        }
+    }
+}
+
+impl EventHandler for ChannelHandler {
+    fn handle(&mut self, event: Event) {
+        if let Event::RDB(obj) = event {
+            if let Some(obj) = OwnedObject::from_object(&obj) {
+                self.send(OwnedEvent::RDB(obj));
+            }
+        }
+    }
+}
+
+/// `Config`中的host以此开头时，表示通过unix socket连接Redis
+pub(crate) const UNIX_SOCKET_PREFIX: &str = "unix://";
+
+/// 根据`Config`建立到Redis的连接，支持TCP、TLS及unix socket
+pub(crate) fn open_transport(config: &Config) -> Result<Box<dyn Transport>> {
+    if let Some(path) = config.host.strip_prefix(UNIX_SOCKET_PREFIX) {
+        return open_unix(config, path);
+    }
+    let addr = io::format_addr(&config.host, config.port);
+    let stream = io::connect_tcp(&config.host, config.port, config.connect_timeout)?;
+    stream.set_read_timeout(config.read_timeout)?;
+    stream.set_write_timeout(config.write_timeout)?;
+    io::set_tcp_options(SockRef::from(&stream), config)?;
+
+    let conn: Box<dyn Transport> = if config.is_tls_enabled {
+        tls::connect(config, stream)?
+    } else {
+        Box::new(stream)
+    };
+    info!("Connected to server {}", &addr);
+    Ok(conn)
+}
+
+/// 通过unix socket连接Redis
+#[cfg(unix)]
+fn open_unix(config: &Config, path: &str) -> Result<Box<dyn Transport>> {
+    if config.is_tls_enabled {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "TLS is not supported over unix socket",
+        ));
+    }
+    let stream = UnixStream::connect(path)?;
+    stream.set_read_timeout(config.read_timeout)?;
+    stream.set_write_timeout(config.write_timeout)?;
+    info!("Connected to server {}", path);
+    Ok(Box::new(stream))
+}
+
+#[cfg(not(unix))]
+fn open_unix(_: &Config, path: &str) -> Result<Box<dyn Transport>> {
+    Err(Error::new(
+        ErrorKind::Unsupported,
+        format!("unix socket is not supported on this platform: {}", path),
+    ))
+}
+
+/// 如果有设置密码，将尝试使用此密码进行认证
+fn auth<T: Read + Write>(conn: &mut T, config: &Config) -> Result<()> {
+    if !config.password.is_empty() {
+        let mut args = Vec::with_capacity(2);
+        if !config.username.is_empty() {
+            args.push(config.username.as_bytes());
+        }
+        args.push(config.password.as_bytes());
+        send(conn, b"AUTH", &args)?;
+        conn.decode_resp()?;
     }
+    Ok(())
 }
 
-struct HeartbeatWorker {
-    handle: Option<JobHandle>,
+/// master通过`REPLCONF GETACK *`要求replica立即汇报offset, `WAIT`命令及failover依赖于此
+pub(crate) fn is_getack<'a>(mut args: impl Iterator<Item = &'a [u8]>) -> bool {
+    matches!((args.next(), args.next()), (Some(name), Some(sub))
+        if name.eq_ignore_ascii_case(b"REPLCONF") && sub.eq_ignore_ascii_case(b"GETACK"))
+}
+
+/// PSYNC的参数，与Redis replica一致，offset为下一个需要接收的字节，即已处理的offset加1
+///
+/// 快照模式或尚无复制进度(offset为-1)时，以`? -1`请求全量同步
+pub(crate) fn psync_args(config: &Config) -> (String, String) {
+    if config.is_snapshot {
+        ("?".to_string(), "-1".to_string())
+    } else if config.repl_offset < 0 {
+        (config.repl_id.clone(), config.repl_offset.to_string())
+    } else {
+        (config.repl_id.clone(), (config.repl_offset + 1).to_string())
+    }
+}
+
+/// 根据`Config`中的`Handshake`生成握手时依次发送的命令，快照模式下追加`REPLCONF rdb-only`
+///
+/// unix socket及自定义的连接没有TCP本地地址，此时若未指定announce的ip及端口，则不发送listening-port及ip-address，
+/// Redis将展示它所看到的对端地址，端口为0
+pub(crate) fn handshake_commands(config: &Config, local_addr: Option<SocketAddr>) -> Vec<Vec<String>> {
+    let handshake = &config.handshake;
+    let mut commands = Vec::new();
+    if let Some(name) = &handshake.client_name {
+        commands.push(vec!["CLIENT".to_string(), "SETNAME".to_string(), name.clone()]);
+    }
+    commands.push(vec!["PING".to_string()]);
+
+    let port = handshake.announce_port.or_else(|| local_addr.map(|addr| addr.port()));
+    match port {
+        Some(port) => commands.push(replconf("listening-port", &port.to_string())),
+        None => info!("No TCP local address, skip REPLCONF listening-port"),
+    }
+    let ip = handshake
+        .announce_ip
+        .clone()
+        .or_else(|| local_addr.map(|addr| addr.ip().to_string()));
+    match ip {
+        Some(ip) => commands.push(replconf("ip-address", &ip)),
+        None => info!("No TCP local address, skip REPLCONF ip-address"),
+    }
+
+    for capa in &handshake.capabilities {
+        commands.push(replconf("capa", capa));
+    }
+    for (option, value) in &handshake.replconf {
+        commands.push(replconf(option, value));
+    }
+    if config.is_snapshot {
+        commands.push(replconf("rdb-only", "1"));
+        if let Some(filter) = &handshake.rdb_filter_only {
+            commands.push(replconf("rdb-filter-only", filter));
+        }
+    }
+    commands
+}
+
+/// 快照模式下，master拒绝`rdb-only`(Redis 7.0以下)时只能进行普通的全量同步
+pub(crate) fn warn_snapshot_fallback(rejected_options: &[RejectedOption]) {
+    if rejected_options
+        .iter()
+        .any(|rejected| rejected.command.starts_with("REPLCONF rdb-"))
+    {
+        warn!("master不支持rdb-only, 快照模式将使用普通的全量同步");
+    }
+}
+
+fn replconf(option: &str, value: &str) -> Vec<String> {
+    vec!["REPLCONF".to_string(), option.to_string(), value.to_string()]
 }
 
 enum NextStep {
     FullSync,
     PartialResync,
+    DualChannel,
     ChangeMode,
     Wait,
 }
@@ -481,8 +919,12 @@ pub struct Builder {
     pub rdb_parser: Option<Rc<RefCell<dyn RDBParser>>>,
     pub event_handler: Option<Rc<RefCell<dyn EventHandler>>>,
     pub module_parser: Option<Rc<RefCell<dyn ModuleParser>>>,
+    pub progress_handler: Option<Rc<RefCell<dyn ProgressHandler>>>,
     pub control_flag: Option<Arc<AtomicBool>>,
     pub thread_pool: Option<Arc<ScheduledThreadPool>>,
+    pub transport: Option<Box<dyn Transport>>,
+    pub rdb_pipeline: Option<usize>,
+    shutdown_handle: Option<ShutdownHandle>,
 }
 
 impl Builder {
@@ -492,8 +934,12 @@ impl Builder {
             rdb_parser: None,
             event_handler: None,
             module_parser: None,
+            progress_handler: None,
             control_flag: None,
             thread_pool: None,
+            transport: None,
+            rdb_pipeline: None,
+            shutdown_handle: None,
         }
     }
 
@@ -513,14 +959,45 @@ impl Builder {
         self.module_parser = Some(parser);
     }
 
+    /// 设置RDB解析进度的处理器，通过`with_rdb_parser`使用自定义的解析器时无效
+    pub fn with_progress_handler(&mut self, handler: Rc<RefCell<dyn ProgressHandler>>) {
+        self.progress_handler = Some(handler);
+    }
+
     pub fn with_control_flag(&mut self, flag: Arc<AtomicBool>) {
         self.control_flag = Some(flag);
     }
 
+    /// 获取用于中止下一个`build`出的`Listener`的[`ShutdownHandle`]，可在`start`之前交给其它线程
+    ///
+    /// 未设置控制变量时将创建一个，之后再通过`with_control_flag`更换控制变量将使此`ShutdownHandle`失效
+    ///
+    /// [`ShutdownHandle`]: struct.ShutdownHandle.html
+    pub fn shutdown_handle(&mut self) -> ShutdownHandle {
+        let running = Arc::clone(self.control_flag.get_or_insert_with(|| Arc::new(AtomicBool::new(true))));
+        match &self.shutdown_handle {
+            Some(handle) if Arc::ptr_eq(&handle.running, &running) => handle.clone(),
+            _ => self.shutdown_handle.insert(ShutdownHandle::new(running)).clone(),
+        }
+    }
+
     pub fn with_thread_pool(&mut self, thread_pool: Arc<ScheduledThreadPool>) {
         self.thread_pool = Option::Some(thread_pool);
     }
 
+    /// 使用自定义的连接，而不是根据`Config`中的地址创建TCP/TLS连接
+    pub fn with_transport(&mut self, transport: Box<dyn Transport>) {
+        self.transport = Option::Some(transport);
+    }
+
+    /// 全量同步时，在独立的线程中读取连接及解析RDB，`buffer_size`为读取线程最多缓存的字节数
+    ///
+    /// 由于`Rc`无法跨线程传递，解析线程不会使用通过`with_module_parser`设置的`ModuleParser`，RDB中的Module将被忽略。
+    /// 通过`with_rdb_parser`使用自定义的解析器时无效
+    pub fn with_rdb_pipeline(&mut self, buffer_size: usize) {
+        self.rdb_pipeline = Some(buffer_size);
+    }
+
     pub fn build(&mut self) -> Listener {
         let config = match &self.config {
             Some(c) => c,
@@ -541,10 +1018,25 @@ impl Builder {
             None => Rc::new(RefCell::new(DefaultRDBParser {
                 running: Arc::clone(&running),
                 module_parser,
+                checksum: config.rdb_checksum,
+                progress_handler: self.progress_handler.clone(),
             })),
             Some(parser) => parser.clone(),
         };
 
+        let rdb_pipeline = match (self.rdb_pipeline, &self.rdb_parser) {
+            (Some(_), Some(_)) => {
+                warn!("使用自定义的RDBParser时不支持流水线解析");
+                None
+            }
+            (Some(buffer_size), None) => Some(Pipeline {
+                buffer_size,
+                checksum: config.rdb_checksum,
+                progress_handler: self.progress_handler.clone(),
+            }),
+            (None, _) => None,
+        };
+
         let event_handler = match &self.event_handler {
             None => Rc::new(RefCell::new(NoOpEventHandler {})),
             Some(handler) => handler.clone(),
@@ -557,20 +1049,19 @@ impl Builder {
 
         Listener {
             config: config.clone(),
-            conn: None,
+            conn: self.transport.take().map(Connection::new),
             rdb_parser,
             event_handler,
-            heartbeat_thread: HeartbeatWorker { handle: None },
+            shutdown_handle: match self.shutdown_handle.take() {
+                Some(handle) if Arc::ptr_eq(&handle.running, &running) => handle,
+                _ => ShutdownHandle::new(Arc::clone(&running)),
+            },
             running,
-            local_ip: None,
-            local_port: None,
             thread_pool,
             repl_offset: Arc::new(AtomicI64::from(config.repl_offset)),
+            command_handler: None,
+            rejected_options: Vec::new(),
+            rdb_pipeline,
         }
     }
 }
-
-enum Stream {
-    Tcp(TcpStream),
-    Tls(TlsStream<TcpStream>),
-}
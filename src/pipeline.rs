@@ -0,0 +1,197 @@
+/*!
+RDB的流水线处理
+
+全量同步时，读取连接、解析RDB(LZF解压、ziplist展开等)及事件处理分别在不同的线程中进行:
+- 读取线程持续读取连接，数据放入有界的缓冲区中，使master的输出缓冲区不会因为解析较慢而堆积
+- 解析线程从缓冲区中读取并解析RDB，解析出的数据通过有界的队列传递
+- 当前线程按解析的顺序将数据交由`EventHandler`处理，同一个key的数据的顺序保持不变
+
+`EventHandler`可以再按照key将数据分发到多个线程中并行处理
+*/
+
+use std::cell::RefCell;
+use std::cmp;
+use std::io::{BufRead, Error, ErrorKind, Result};
+use std::rc::Rc;
+use std::sync::atomic::AtomicBool;
+use std::sync::mpsc::{self, SyncSender};
+use std::sync::Arc;
+use std::thread;
+
+use log::warn;
+
+use crate::io::{self, ChunkReader, Connection};
+use crate::rdb::{ChecksumPolicy, DefaultRDBParser, OwnedObject, Progress};
+use crate::{Event, EventHandler, ProgressHandler, RDBParser};
+
+// 每次从连接中读取的数据不超过此大小，与`Connection`读缓冲区的大小相同
+const CHUNK_SIZE: usize = 8 * 1024;
+// 已解析但尚未处理的数据的最大数量
+const OBJECT_CAPACITY: usize = 1024;
+
+pub(crate) struct Pipeline {
+    /// 读取线程最多缓存的字节数
+    pub(crate) buffer_size: usize,
+    pub(crate) checksum: ChecksumPolicy,
+    pub(crate) progress_handler: Option<Rc<RefCell<dyn ProgressHandler>>>,
+}
+
+enum Message {
+    Object(OwnedObject),
+    Progress(Progress),
+}
+
+/// 在解析线程中将解析结果发送到当前线程
+struct Forwarder(SyncSender<Message>);
+
+impl EventHandler for Forwarder {
+    fn handle(&mut self, event: Event) {
+        if let Event::RDB(obj) = event {
+            if let Some(obj) = OwnedObject::from_object(&obj) {
+                // 当前线程已退出时，解析线程将在读取完毕之后结束
+                let _ = self.0.send(Message::Object(obj));
+            }
+        }
+    }
+}
+
+impl ProgressHandler for Forwarder {
+    fn handle(&mut self, progress: &Progress) {
+        let _ = self.0.send(Message::Progress(progress.clone()));
+    }
+}
+
+impl Pipeline {
+    /// 接收并解析RDB，`length`为-1时表示无盘复制，此时RDB以`eof_mark`结尾
+    ///
+    /// 读取线程只读取到RDB的结尾，之后的数据仍保留在`conn`中
+    pub(crate) fn receive_rdb(
+        &self, conn: &mut Connection, length: i64, eof_mark: Option<&[u8]>, running: &Arc<AtomicBool>,
+        handler: &mut dyn EventHandler,
+    ) -> Result<()> {
+        let (chunk_sender, chunks) = mpsc::sync_channel(cmp::max(self.buffer_size / CHUNK_SIZE, 1));
+        let (sender, messages) = mpsc::sync_channel(OBJECT_CAPACITY);
+        // 解析失败时关闭连接，使读取线程不再等待剩余的数据
+        let conn_clone = conn.try_clone().ok();
+        let checksum = self.checksum;
+        let report_progress = self.progress_handler.is_some();
+        let running = Arc::clone(running);
+
+        thread::scope(|scope| {
+            let reader = thread::Builder::new()
+                .name("rdb-reader".to_string())
+                .spawn_scoped(scope, move || read_rdb(conn, length, eof_mark, chunk_sender))?;
+            let decoder = thread::Builder::new()
+                .name("rdb-decoder".to_string())
+                .spawn_scoped(scope, move || {
+                    let mut input = ChunkReader::new(chunks, Vec::new());
+                    let progress_handler: Option<Rc<RefCell<dyn ProgressHandler>>> = if report_progress {
+                        Some(Rc::new(RefCell::new(Forwarder(sender.clone()))))
+                    } else {
+                        None
+                    };
+                    let mut parser = DefaultRDBParser {
+                        running,
+                        module_parser: None,
+                        checksum,
+                        progress_handler,
+                    };
+                    parser.parse(&mut input, length, &mut Forwarder(sender))?;
+                    if let Some(eof_mark) = eof_mark {
+                        io::verify_eof_mark(&mut input, eof_mark)?;
+                    }
+                    Ok(())
+                })?;
+
+            for message in messages {
+                match message {
+                    Message::Object(obj) => handler.handle(Event::RDB(obj.as_object())),
+                    Message::Progress(progress) => {
+                        if let Some(progress_handler) = &self.progress_handler {
+                            progress_handler.borrow_mut().handle(&progress);
+                        }
+                    }
+                }
+            }
+
+            let result = decoder.join().unwrap_or_else(|_| Err(panicked("rdb-decoder")));
+            if result.is_err() {
+                if let Some(Err(err)) = conn_clone.map(|conn| conn.shutdown()) {
+                    warn!("关闭连接失败: {}", err);
+                }
+            }
+            // 两者都失败时，解析线程的错误更能说明原因
+            let read = reader.join().unwrap_or_else(|_| Err(panicked("rdb-reader")));
+            result.and(read)
+        })
+    }
+}
+
+fn panicked(name: &str) -> Error {
+    Error::new(ErrorKind::Interrupted, format!("thread {} panicked", name))
+}
+
+/// 将RDB按块发送到解析线程，发送失败说明解析线程已结束
+fn read_rdb(
+    conn: &mut Connection, length: i64, eof_mark: Option<&[u8]>, chunks: SyncSender<Result<Vec<u8>>>,
+) -> Result<()> {
+    let result = match eof_mark {
+        Some(eof_mark) if length == -1 => read_until_mark(conn, eof_mark, &chunks),
+        _ => read_exact(conn, length as usize, &chunks),
+    };
+    match result {
+        Err(err) => {
+            let _ = chunks.send(Err(Error::new(err.kind(), err.to_string())));
+            Err(err)
+        }
+        Ok(()) => Ok(()),
+    }
+}
+
+fn read_exact(conn: &mut Connection, mut remaining: usize, chunks: &SyncSender<Result<Vec<u8>>>) -> Result<()> {
+    while remaining > 0 {
+        let buf = fill_buf(conn)?;
+        let size = cmp::min(buf.len(), remaining);
+        if chunks.send(Ok(buf[..size].to_vec())).is_err() {
+            return Ok(());
+        }
+        conn.consume(size);
+        remaining -= size;
+    }
+    Ok(())
+}
+
+/// 读取到`eof_mark`为止(包括`eof_mark`)，`eof_mark`可能跨越两次读取
+fn read_until_mark(conn: &mut Connection, eof_mark: &[u8], chunks: &SyncSender<Result<Vec<u8>>>) -> Result<()> {
+    let mut tail = Vec::with_capacity(eof_mark.len());
+    loop {
+        let buf = fill_buf(conn)?;
+        let mut window = tail;
+        let tail_len = window.len();
+        window.extend_from_slice(buf);
+        let found = window.windows(eof_mark.len()).position(|bytes| bytes == eof_mark);
+        let size = match found {
+            Some(pos) => pos + eof_mark.len() - tail_len,
+            None => buf.len(),
+        };
+        if chunks.send(Ok(buf[..size].to_vec())).is_err() {
+            return Ok(());
+        }
+        conn.consume(size);
+        if found.is_some() {
+            return Ok(());
+        }
+        tail = window.split_off(window.len().saturating_sub(eof_mark.len() - 1));
+    }
+}
+
+fn fill_buf(conn: &mut Connection) -> Result<&[u8]> {
+    let buf = conn.fill_buf()?;
+    if buf.is_empty() {
+        return Err(Error::new(
+            ErrorKind::UnexpectedEof,
+            "Connection closed while reading RDB",
+        ));
+    }
+    Ok(buf)
+}
@@ -6,22 +6,24 @@ use std::any::Any;
 use std::cmp;
 use std::collections::BTreeMap;
 use std::fmt::{Debug, Error, Formatter};
-use std::io::{Cursor, Read, Result};
+use std::io::{self, Cursor, ErrorKind, Read, Result};
 use std::sync::atomic::{AtomicBool, Ordering};
 
 use byteorder::{BigEndian, LittleEndian, ReadBytesExt};
-use log::info;
+use log::{info, warn};
 
 use crate::cmd::connection::SELECT;
 use crate::cmd::Command;
+use crate::crc64;
 use crate::iter::{IntSetIter, Iter, QuickListIter, SortedSetIter, StrValIter, ZipListIter, ZipMapIter};
-use crate::{lzf, to_string, Event, EventHandler, ModuleParser, RDBParser};
+use crate::{lzf, to_string, Event, EventHandler, ModuleParser, ProgressHandler, RDBParser};
 use std::cell::RefCell;
 use std::f64::{INFINITY, NAN, NEG_INFINITY};
 use std::iter::FromIterator;
 use std::rc::Rc;
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 /// 一些解析RDB数据的方法
 pub trait RDBDecode: Read {
@@ -132,10 +134,87 @@ impl<R: Read + ?Sized> RDBDecode for R {}
 pub(crate) struct DefaultRDBParser {
     pub(crate) running: Arc<AtomicBool>,
     pub(crate) module_parser: Option<Rc<RefCell<dyn ModuleParser>>>,
+    pub(crate) checksum: ChecksumPolicy,
+    pub(crate) progress_handler: Option<Rc<RefCell<dyn ProgressHandler>>>,
+}
+
+// 向ProgressHandler汇报进度的最小间隔
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(1);
+
+/// RDB的解析进度，由[`ProgressHandler`]接收
+///
+/// 无盘复制时RDB的总字节数未知，此时可通过`total_keys`(来自RDB中的RESIZEDB)估算进度
+///
+/// [`ProgressHandler`]: ../trait.ProgressHandler.html
+#[derive(Debug, Clone)]
+pub struct Progress {
+    /// 已读取的字节数
+    pub bytes_read: u64,
+    /// RDB的总字节数，无盘复制时为None
+    pub total_bytes: Option<u64>,
+    /// 各db已解析的key数量
+    pub keys: BTreeMap<isize, u64>,
+    /// 各db的key总数，来自RDB中的RESIZEDB，旧版本的RDB中没有此信息
+    pub total_keys: BTreeMap<isize, u64>,
+    /// 开始解析之后经过的时间
+    pub elapsed: Duration,
+    /// 是否已解析完毕
+    pub finished: bool,
+}
+
+// 读取RDB的同时统计读取的字节数，开启校验时计算CRC64
+struct RdbReader<'a> {
+    inner: &'a mut dyn Read,
+    crc: Option<u64>,
+    bytes_read: u64,
+}
+
+impl Read for RdbReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let size = self.inner.read(buf)?;
+        if let Some(crc) = self.crc {
+            self.crc = Some(crc64::update(crc, &buf[..size]));
+        }
+        self.bytes_read += size as u64;
+        Ok(size)
+    }
+}
+
+/// RDB末尾CRC64校验和的处理方式
+///
+/// 校验和按Redis的算法(Jones多项式)对RDB中校验和之前的所有字节进行计算，RDB版本低于5时没有校验和
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChecksumPolicy {
+    /// 不计算校验和
+    #[default]
+    Ignore,
+    /// 校验和不一致时输出警告日志
+    Warn,
+    /// 校验和不一致时返回`InvalidData`错误，不会产生`Object::EOR`事件
+    Error,
 }
 
 impl RDBParser for DefaultRDBParser {
-    fn parse(&mut self, input: &mut dyn Read, _: i64, event_handler: &mut dyn EventHandler) -> Result<()> {
+    fn parse(&mut self, rdb: &mut dyn Read, length: i64, event_handler: &mut dyn EventHandler) -> Result<()> {
+        let mut input = RdbReader {
+            inner: rdb,
+            crc: if self.checksum == ChecksumPolicy::Ignore {
+                None
+            } else {
+                Some(0)
+            },
+            bytes_read: 0,
+        };
+        let start = Instant::now();
+        let mut last_report = start;
+        let mut progress = Progress {
+            bytes_read: 0,
+            total_bytes: if length > 0 { Some(length as u64) } else { None },
+            keys: BTreeMap::new(),
+            total_keys: BTreeMap::new(),
+            elapsed: Duration::from_secs(0),
+            finished: false,
+        };
         event_handler.handle(Event::RDB(Object::BOR));
         let mut bytes = vec![0; 5];
         // 开头5个字节: REDIS
@@ -172,6 +251,7 @@ impl RDBParser for DefaultRDBParser {
                 RDB_OPCODE_RESIZEDB => {
                     let (total, _) = input.read_length()?;
                     info!("db[{}] total keys: {}", db, total);
+                    progress.total_keys.insert(db, total as u64);
                     let (expired, _) = input.read_length()?;
                     info!("db[{}] expired keys: {}", db, expired);
                 }
@@ -189,16 +269,16 @@ impl RDBParser for DefaultRDBParser {
                             let val = input.read_u8()?;
                             let value_type = input.read_u8()?;
                             meta.evict = Option::Some((EvictType::LFU, val as i64));
-                            self.read_object(input, value_type, event_handler, &meta)?;
+                            self.read_object(&mut input, value_type, event_handler, &meta)?;
                         }
                         RDB_OPCODE_IDLE => {
                             let (val, _) = input.read_length()?;
                             let value_type = input.read_u8()?;
                             meta.evict = Option::Some((EvictType::LRU, val as i64));
-                            self.read_object(input, value_type, event_handler, &meta)?;
+                            self.read_object(&mut input, value_type, event_handler, &meta)?;
                         }
                         _ => {
-                            self.read_object(input, value_type, event_handler, &meta)?;
+                            self.read_object(&mut input, value_type, event_handler, &meta)?;
                         }
                     }
                 }
@@ -206,28 +286,44 @@ impl RDBParser for DefaultRDBParser {
                     let val = input.read_u8()?;
                     let value_type = input.read_u8()?;
                     meta.evict = Option::Some((EvictType::LFU, val as i64));
-                    self.read_object(input, value_type, event_handler, &meta)?;
+                    self.read_object(&mut input, value_type, event_handler, &meta)?;
                 }
                 RDB_OPCODE_IDLE => {
                     let (val, _) = input.read_length()?;
                     meta.evict = Option::Some((EvictType::LRU, val as i64));
                     let value_type = input.read_u8()?;
-                    self.read_object(input, value_type, event_handler, &meta)?;
+                    self.read_object(&mut input, value_type, event_handler, &meta)?;
                 }
                 RDB_OPCODE_MODULE_AUX => {
                     input.read_length()?;
-                    self.rdb_load_check_module_value(input)?;
+                    self.rdb_load_check_module_value(&mut input)?;
                 }
                 RDB_OPCODE_EOF => {
                     if rdb_version >= 5 {
-                        input.read_integer(8, true)?;
+                        let digest = input.crc.unwrap_or_default();
+                        let checksum = input.read_u64::<LittleEndian>()?;
+                        self.verify_checksum(digest, checksum)?;
+                    } else if self.checksum != ChecksumPolicy::Ignore {
+                        warn!("RDB version {} has no checksum", rdb_version);
                     }
+                    progress.finished = true;
+                    self.report_progress(&mut progress, input.bytes_read, start);
                     break;
                 }
                 _ => {
-                    self.read_object(input, data_type, event_handler, &meta)?;
+                    self.read_object(&mut input, data_type, event_handler, &meta)?;
                 }
             };
+            if !matches!(
+                data_type,
+                RDB_OPCODE_AUX | RDB_OPCODE_SELECTDB | RDB_OPCODE_RESIZEDB | RDB_OPCODE_MODULE_AUX | RDB_OPCODE_EOF
+            ) {
+                *progress.keys.entry(db).or_insert(0) += 1;
+                if last_report.elapsed() >= PROGRESS_INTERVAL {
+                    self.report_progress(&mut progress, input.bytes_read, start);
+                    last_report = Instant::now();
+                }
+            }
         }
         event_handler.handle(Event::RDB(Object::EOR));
         Ok(())
@@ -235,6 +331,35 @@ impl RDBParser for DefaultRDBParser {
 }
 
 impl DefaultRDBParser {
+    fn report_progress(&self, progress: &mut Progress, bytes_read: u64, start: Instant) {
+        if let Some(handler) = &self.progress_handler {
+            progress.bytes_read = bytes_read;
+            progress.elapsed = start.elapsed();
+            handler.borrow_mut().handle(progress);
+        }
+    }
+
+    // 比较计算出的校验和与RDB末尾的校验和，Redis关闭了rdbchecksum时末尾为0
+    fn verify_checksum(&self, digest: u64, checksum: u64) -> Result<()> {
+        if self.checksum == ChecksumPolicy::Ignore {
+            return Ok(());
+        }
+        if checksum == 0 {
+            warn!("RDB checksum is disabled by the source");
+            return Ok(());
+        }
+        if digest == checksum {
+            info!("RDB checksum verified: {:016x}", checksum);
+            return Ok(());
+        }
+        let message = format!("RDB checksum mismatch, expected {:016x}, got {:016x}", checksum, digest);
+        if self.checksum == ChecksumPolicy::Error {
+            return Err(io::Error::new(ErrorKind::InvalidData, message));
+        }
+        warn!("{}", message);
+        Ok(())
+    }
+
     // 根据传入的数据类型，从流中读取对应类型的数据
     fn read_object(
         &mut self, input: &mut dyn Read, value_type: u8, event_handler: &mut dyn EventHandler, meta: &Meta,
@@ -888,6 +1013,116 @@ pub enum Object<'a> {
     EOR,
 }
 
+/// [`Object`]的所有权版本，可在线程及异步任务之间传递
+///
+/// Module类型的数据无法转换，不包含在内
+///
+/// [`Object`]: enum.Object.html
+#[derive(Debug, Clone)]
+pub enum OwnedObject {
+    /// 代表Redis中的String类型数据
+    String { key: Vec<u8>, value: Vec<u8>, meta: Meta },
+    /// 代表Redis中的List类型数据
+    List {
+        key: Vec<u8>,
+        values: Vec<Vec<u8>>,
+        meta: Meta,
+    },
+    /// 代表Redis中的Set类型数据
+    Set {
+        key: Vec<u8>,
+        members: Vec<Vec<u8>>,
+        meta: Meta,
+    },
+    /// 代表Redis中的SortedSet类型数据
+    SortedSet { key: Vec<u8>, items: Vec<Item>, meta: Meta },
+    /// 代表Redis中的Hash类型数据
+    Hash {
+        key: Vec<u8>,
+        fields: Vec<Field>,
+        meta: Meta,
+    },
+    /// 代表Redis中的Stream类型数据
+    Stream {
+        key: Vec<u8>,
+        entries: BTreeMap<ID, Entry>,
+        groups: Vec<Group>,
+        meta: Meta,
+    },
+    /// 代表rdb数据解析开始
+    BOR,
+    /// 代表rdb数据解析完毕
+    EOR,
+}
+
+impl OwnedObject {
+    /// 复制`Object`中的数据，Module类型的数据返回None
+    pub fn from_object(obj: &Object) -> Option<OwnedObject> {
+        let owned = match obj {
+            Object::String(kv) => OwnedObject::String {
+                key: kv.key.to_vec(),
+                value: kv.value.to_vec(),
+                meta: kv.meta.clone(),
+            },
+            Object::List(list) => OwnedObject::List {
+                key: list.key.to_vec(),
+                values: list.values.to_vec(),
+                meta: list.meta.clone(),
+            },
+            Object::Set(set) => OwnedObject::Set {
+                key: set.key.to_vec(),
+                members: set.members.to_vec(),
+                meta: set.meta.clone(),
+            },
+            Object::SortedSet(set) => OwnedObject::SortedSet {
+                key: set.key.to_vec(),
+                items: set.items.to_vec(),
+                meta: set.meta.clone(),
+            },
+            Object::Hash(hash) => OwnedObject::Hash {
+                key: hash.key.to_vec(),
+                fields: hash.fields.to_vec(),
+                meta: hash.meta.clone(),
+            },
+            Object::Stream(key, stream) => OwnedObject::Stream {
+                key: key.clone(),
+                entries: stream.entries.clone(),
+                groups: stream.groups.clone(),
+                meta: stream.meta.clone(),
+            },
+            Object::Module(..) => return None,
+            Object::BOR => OwnedObject::BOR,
+            Object::EOR => OwnedObject::EOR,
+        };
+        Some(owned)
+    }
+    /// 以借用的方式转换为`Object`，Stream中的数据会被复制
+    pub fn as_object(&self) -> Object<'_> {
+        match self {
+            OwnedObject::String { key, value, meta } => Object::String(KeyValue { key, value, meta }),
+            OwnedObject::List { key, values, meta } => Object::List(List { key, values, meta }),
+            OwnedObject::Set { key, members, meta } => Object::Set(Set { key, members, meta }),
+            OwnedObject::SortedSet { key, items, meta } => Object::SortedSet(SortedSet { key, items, meta }),
+            OwnedObject::Hash { key, fields, meta } => Object::Hash(Hash { key, fields, meta }),
+            OwnedObject::Stream {
+                key,
+                entries,
+                groups,
+                meta,
+            } => Object::Stream(
+                key.clone(),
+                Stream {
+                    entries: entries.clone(),
+                    groups: groups.clone(),
+                    meta,
+                },
+            ),
+            OwnedObject::BOR => Object::BOR,
+            OwnedObject::EOR => Object::EOR,
+        }
+    }
+}
+
 pub trait Module {
     fn as_any(&self) -> &dyn Any;
 }
@@ -899,7 +1134,7 @@ impl Debug for dyn Module {
 }
 
 /// 数据的元信息, 包括数据过期类型, 内存驱逐类型, 数据所属的db
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Meta {
     /// 数据所属的db
     pub db: isize,
@@ -910,7 +1145,7 @@ pub struct Meta {
 }
 
 /// 过期类型
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum ExpireType {
     /// 以秒计算过期时间
     Second,
@@ -919,7 +1154,7 @@ pub enum ExpireType {
 }
 
 /// 内存驱逐类型
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum EvictType {
     /// Least Recently Used
     LRU,
@@ -972,7 +1207,7 @@ pub struct SortedSet<'a> {
 }
 
 /// SortedSet中的一条元素
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Item {
     /// 元素值
     pub member: Vec<u8>,
@@ -992,7 +1227,7 @@ pub struct Hash<'a> {
 }
 
 /// Hash类型数据中的一个字段
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Field {
     /// 字段名
     pub name: Vec<u8>,
@@ -1079,14 +1314,14 @@ impl Ord for ID {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Entry {
     pub id: ID,
     pub deleted: bool,
     pub fields: BTreeMap<Vec<u8>, Vec<u8>>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Group {
     pub name: Vec<u8>,
     pub last_id: ID,
@@ -2,15 +2,21 @@
 Redis Serialization Protocol相关的解析代码
 */
 
-use std::io::{Read, Result};
+use std::cmp;
+use std::io::{BufRead, Error, ErrorKind, Read, Result};
+use std::iter;
+use std::ops::Range;
 
 use byteorder::ReadBytesExt;
+#[cfg(feature = "async-tokio")]
+use tokio::io::AsyncReadExt;
 
-use crate::to_string;
+use crate::cmd::{self, RawCommand};
+use crate::{to_string, EventHandler};
 
 /// Redis Serialization Protocol解析
 pub trait RespDecode: Read {
-    /// 读取并解析Redis响应
+    /// 读取并解析Redis响应，支持RESP2及RESP3
     fn decode_resp(&mut self) -> Result<Resp> {
         match self.decode_type()? {
             Type::String => Ok(Resp::String(self.decode_string()?)),
@@ -18,6 +24,37 @@ pub trait RespDecode: Read {
             Type::Error => Ok(Resp::Error(self.decode_string()?)),
             Type::BulkString => self.decode_bulk_string(),
             Type::Array => self.decode_array(),
+            Type::Null => {
+                self.decode_string()?;
+                Ok(Resp::Null)
+            }
+            Type::Double => {
+                let s = self.decode_string()?;
+                parse_double(&s).map(Resp::Double)
+            }
+            Type::Boolean => {
+                let s = self.decode_string()?;
+                parse_boolean(&s).map(Resp::Boolean)
+            }
+            Type::BigNumber => Ok(Resp::BigNumber(self.decode_string()?)),
+            Type::BlobError => match self.decode_bulk_string()? {
+                Resp::BulkBytes(bytes) => Ok(Resp::Error(to_string(bytes))),
+                resp => Ok(resp),
+            },
+            Type::Verbatim => match self.decode_bulk_string()? {
+                Resp::BulkBytes(bytes) => parse_verbatim(bytes),
+                resp => Ok(resp),
+            },
+            Type::Map => {
+                let len = self.decode_length()?;
+                let mut map = Vec::with_capacity(cmp::max(len, 0) as usize);
+                for _ in 0..len {
+                    map.push((self.decode_resp()?, self.decode_resp()?));
+                }
+                Ok(Resp::Map(map))
+            }
+            Type::Set => Ok(Resp::Set(self.decode_elements()?)),
+            Type::Push => Ok(Resp::Push(self.decode_elements()?)),
         }
     }
     /// 读取解析Redis响应的类型
@@ -27,14 +64,7 @@ pub trait RespDecode: Read {
             if b == LF {
                 continue;
             } else {
-                match b {
-                    PLUS => return Ok(Type::String),
-                    MINUS => return Ok(Type::Error),
-                    COLON => return Ok(Type::Int),
-                    DOLLAR => return Ok(Type::BulkString),
-                    STAR => return Ok(Type::Array),
-                    _ => panic!("Unexpected Data Type: {}", b),
-                }
+                return to_type(b);
             }
         }
     }
@@ -63,53 +93,425 @@ pub trait RespDecode: Read {
         return Ok(Resp::Int(i));
     }
 
-    /// 解析Bulk String响应
+    /// 解析Bulk String响应，长度为-1时返回`Resp::Null`
     fn decode_bulk_string(&mut self) -> Result<Resp> {
-        let r = self.decode_int()?;
-        if let Resp::Int(i) = r {
-            if i > 0 {
-                let mut buf = vec![0; i as usize];
-                self.read_exact(&mut buf)?;
-                let mut end = vec![0; 2];
-                self.read_exact(&mut end)?;
-                if !end.eq(&[CR, LF]) {
-                    panic!("Expected CRLF");
-                } else {
-                    return Ok(Resp::BulkBytes(buf));
-                }
-            } else {
-                self.read_exact(&mut [0; 2])?;
-                return Ok(Resp::BulkBytes(vec![0; 0]));
-            }
-        } else {
-            panic!("Expected Int Response");
+        let len = self.decode_length()?;
+        if len == -1 {
+            // null bulk string之后没有CRLF
+            return Ok(Resp::Null);
         }
+        let mut buf = vec![0; len as usize];
+        self.read_exact(&mut buf)?;
+        let mut end = [0; 2];
+        self.read_exact(&mut end)?;
+        if end != [CR, LF] {
+            panic!("Expected CRLF");
+        }
+        Ok(Resp::BulkBytes(buf))
     }
 
-    /// 解析Array响应
+    /// 解析Array响应，长度为-1时(如对不存在的key执行SENTINEL get-master-addr-by-name)返回`Resp::Null`
     fn decode_array(&mut self) -> Result<Resp> {
-        let r = self.decode_int()?;
-        if let Resp::Int(i) = r {
-            let mut arr = Vec::with_capacity(i as usize);
-            for _ in 0..i {
-                let resp = self.decode_resp()?;
-                arr.push(resp);
-            }
-            return Ok(Resp::Array(arr));
-        } else {
-            panic!("Expected Int Response");
+        let len = self.decode_length()?;
+        if len == -1 {
+            return Ok(Resp::Null);
+        }
+        let mut arr = Vec::with_capacity(len as usize);
+        for _ in 0..len {
+            arr.push(self.decode_resp()?);
+        }
+        Ok(Resp::Array(arr))
+    }
+
+    /// 解析Bulk String、Array等类型的长度，只允许-1(表示null)及非负数
+    fn decode_length(&mut self) -> Result<i64> {
+        let s = self.decode_string()?;
+        parse_length(&s)
+    }
+
+    /// 解析Set、Push等类型中的元素
+    fn decode_elements(&mut self) -> Result<Vec<Resp>> {
+        let len = self.decode_length()?;
+        let mut elements = Vec::with_capacity(cmp::max(len, 0) as usize);
+        for _ in 0..len {
+            elements.push(self.decode_resp()?);
         }
+        Ok(elements)
     }
 }
 
 impl<R: Read + ?Sized> RespDecode for R {}
 
+/// 可重复使用的命令帧，用于读取master传播过来的命令
+///
+/// 与[`RespDecode::decode_resp`]不同，所有参数保存在同一个缓冲区中，参数只是其中的一段，
+/// 读取下一条命令时复用已分配的内存，因此读取命令时基本上不再分配内存
+///
+/// [`RespDecode::decode_resp`]: trait.RespDecode.html#method.decode_resp
+#[derive(Debug, Default)]
+pub struct CommandFrame {
+    buf: Vec<u8>,
+    args: Vec<Range<usize>>,
+    line: Vec<u8>,
+}
+
+impl CommandFrame {
+    pub fn new() -> CommandFrame {
+        Default::default()
+    }
+
+    /// 读取一条命令，即由Bulk String组成的Array，返回此命令在协议中占用的字节数
+    ///
+    /// 命令之前的单独的LF(master在RDB生成期间发送的心跳)会被跳过，但同样计入字节数
+    pub fn read_from<R: BufRead + ?Sized>(&mut self, input: &mut R) -> Result<usize> {
+        self.buf.clear();
+        self.args.clear();
+        let mut size = self.read_line(input)?;
+        while self.line == [LF] {
+            size += self.read_line(input)?;
+        }
+        let len = self.parse_header(STAR)?;
+        for _ in 0..len {
+            size += self.read_line(input)?;
+            let len = self.parse_header(DOLLAR)?;
+            let start = self.buf.len();
+            self.buf.resize(start + len, 0);
+            input.read_exact(&mut self.buf[start..])?;
+            let mut end = [0; 2];
+            input.read_exact(&mut end)?;
+            if end != [CR, LF] {
+                return Err(invalid_data("Expected CRLF"));
+            }
+            self.args.push(start..start + len);
+            size += len + 2;
+        }
+        Ok(size)
+    }
+
+    fn read_line<R: BufRead + ?Sized>(&mut self, input: &mut R) -> Result<usize> {
+        self.line.clear();
+        match input.read_until(LF, &mut self.line)? {
+            0 => Err(Error::new(ErrorKind::UnexpectedEof, "Connection closed")),
+            size => Ok(size),
+        }
+    }
+
+    // 解析形如`*3\r\n`、`$5\r\n`的行，返回其中的长度
+    fn parse_header(&self, expected: u8) -> Result<usize> {
+        let line = &self.line;
+        if line.len() >= 4 && line[0] == expected && line.ends_with(&[CR, LF]) {
+            if let Some(len) = std::str::from_utf8(&line[1..line.len() - 2])
+                .ok()
+                .and_then(|len| len.parse::<usize>().ok())
+            {
+                return Ok(len);
+            }
+        }
+        Err(invalid_data(format!(
+            "Unexpected command frame: {}",
+            String::from_utf8_lossy(line).trim_end()
+        )))
+    }
+
+    /// 以`cmd`的内容填充此命令帧，复用已分配的内存
+    pub(crate) fn fill(&mut self, cmd: &RawCommand) {
+        self.buf.clear();
+        self.args.clear();
+        for arg in iter::once(cmd.name.as_bytes()).chain(cmd.args.iter().map(Vec::as_slice)) {
+            let start = self.buf.len();
+            self.buf.extend_from_slice(arg);
+            self.args.push(start..self.buf.len());
+        }
+    }
+
+    /// 参数的个数，包括命令名
+    pub fn len(&self) -> usize {
+        self.args.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.args.is_empty()
+    }
+
+    /// 命令名，保留master发送时的大小写
+    pub fn name(&self) -> Option<&[u8]> {
+        self.arg(0)
+    }
+
+    /// 第`index`个参数，`0`为命令名
+    pub fn arg(&self, index: usize) -> Option<&[u8]> {
+        self.args.get(index).map(|range| &self.buf[range.clone()])
+    }
+
+    /// 依次返回包括命令名在内的所有参数
+    pub fn args(&self) -> FrameArgs<'_> {
+        FrameArgs {
+            buf: &self.buf,
+            ranges: self.args.iter(),
+        }
+    }
+
+    /// 将此命令解析为[`Command`]，并交由`handler`处理，与[`RawCommand::dispatch`]相同
+    ///
+    /// [`Command`]: ../cmd/enum.Command.html
+    /// [`RawCommand::dispatch`]: ../cmd/struct.RawCommand.html#method.dispatch
+    pub fn dispatch(&self, handler: &mut dyn EventHandler) {
+        let mut args = self.args();
+        if let Some(name) = args.next() {
+            cmd::dispatch(name, cmd::Args::Frame(args), handler);
+        }
+    }
+}
+
+/// [`CommandFrame`]中参数的迭代器
+///
+/// [`CommandFrame`]: struct.CommandFrame.html
+#[derive(Debug, Clone)]
+pub struct FrameArgs<'a> {
+    buf: &'a [u8],
+    ranges: std::slice::Iter<'a, Range<usize>>,
+}
+
+impl<'a> Iterator for FrameArgs<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<&'a [u8]> {
+        self.ranges.next().map(|range| &self.buf[range.clone()])
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.ranges.size_hint()
+    }
+}
+
+#[cfg(feature = "async-tokio")]
+type DecodeFuture<'a, T> = std::pin::Pin<Box<dyn std::future::Future<Output = Result<T>> + Send + 'a>>;
+
+/// 基于tokio `AsyncRead`的Redis Serialization Protocol解析，与[`RespDecode`]一一对应
+///
+/// 数据格式错误时返回`InvalidData`错误，而不是panic
+///
+/// [`RespDecode`]: trait.RespDecode.html
+#[cfg(feature = "async-tokio")]
+pub trait AsyncRespDecode: tokio::io::AsyncRead + Unpin + Send {
+    /// 读取并解析Redis响应，支持RESP2及RESP3
+    fn decode_resp(&mut self) -> DecodeFuture<'_, Resp> {
+        Box::pin(async move {
+            match self.decode_type().await? {
+                Type::String => Ok(Resp::String(self.decode_string().await?)),
+                Type::Int => self.decode_int().await,
+                Type::Error => Ok(Resp::Error(self.decode_string().await?)),
+                Type::BulkString => self.decode_bulk_string().await,
+                Type::Array => self.decode_array().await,
+                Type::Null => {
+                    self.decode_string().await?;
+                    Ok(Resp::Null)
+                }
+                Type::Double => {
+                    let s = self.decode_string().await?;
+                    parse_double(&s).map(Resp::Double)
+                }
+                Type::Boolean => {
+                    let s = self.decode_string().await?;
+                    parse_boolean(&s).map(Resp::Boolean)
+                }
+                Type::BigNumber => Ok(Resp::BigNumber(self.decode_string().await?)),
+                Type::BlobError => match self.decode_bulk_string().await? {
+                    Resp::BulkBytes(bytes) => Ok(Resp::Error(to_string(bytes))),
+                    resp => Ok(resp),
+                },
+                Type::Verbatim => match self.decode_bulk_string().await? {
+                    Resp::BulkBytes(bytes) => parse_verbatim(bytes),
+                    resp => Ok(resp),
+                },
+                Type::Map => {
+                    let len = self.decode_length().await?;
+                    let mut map = Vec::with_capacity(cmp::max(len, 0) as usize);
+                    for _ in 0..len {
+                        map.push((self.decode_resp().await?, self.decode_resp().await?));
+                    }
+                    Ok(Resp::Map(map))
+                }
+                Type::Set => Ok(Resp::Set(self.decode_elements().await?)),
+                Type::Push => Ok(Resp::Push(self.decode_elements().await?)),
+            }
+        })
+    }
+
+    /// 读取解析Redis响应的类型
+    fn decode_type(&mut self) -> DecodeFuture<'_, Type> {
+        Box::pin(async move {
+            loop {
+                match self.read_u8().await? {
+                    LF => continue,
+                    b => return to_type(b),
+                }
+            }
+        })
+    }
+
+    /// 解析Simple String响应
+    fn decode_string(&mut self) -> DecodeFuture<'_, String> {
+        Box::pin(async move {
+            let mut buf = vec![];
+            loop {
+                let byte = self.read_u8().await?;
+                if byte != CR {
+                    buf.push(byte);
+                } else {
+                    break;
+                }
+            }
+            if self.read_u8().await? == LF {
+                Ok(to_string(buf))
+            } else {
+                Err(invalid_data("Expect LF after CR"))
+            }
+        })
+    }
+
+    /// 解析Integer响应
+    fn decode_int(&mut self) -> DecodeFuture<'_, Resp> {
+        Box::pin(async move {
+            let s = self.decode_string().await?;
+            match s.parse::<i64>() {
+                Ok(i) => Ok(Resp::Int(i)),
+                Err(_) => Err(invalid_data(format!("Expect integer, but got {}", s))),
+            }
+        })
+    }
+
+    /// 解析Bulk String响应，长度为-1时返回`Resp::Null`
+    fn decode_bulk_string(&mut self) -> DecodeFuture<'_, Resp> {
+        Box::pin(async move {
+            let len = self.decode_length().await?;
+            if len == -1 {
+                return Ok(Resp::Null);
+            }
+            let mut buf = vec![0; len as usize];
+            self.read_exact(&mut buf).await?;
+            let mut end = [0; 2];
+            self.read_exact(&mut end).await?;
+            if end != [CR, LF] {
+                return Err(invalid_data("Expected CRLF"));
+            }
+            Ok(Resp::BulkBytes(buf))
+        })
+    }
+
+    /// 解析Array响应，长度为-1时返回`Resp::Null`
+    fn decode_array(&mut self) -> DecodeFuture<'_, Resp> {
+        Box::pin(async move {
+            let len = self.decode_length().await?;
+            if len == -1 {
+                return Ok(Resp::Null);
+            }
+            let mut arr = Vec::with_capacity(len as usize);
+            for _ in 0..len {
+                arr.push(self.decode_resp().await?);
+            }
+            Ok(Resp::Array(arr))
+        })
+    }
+
+    /// 解析Bulk String、Array等类型的长度，只允许-1(表示null)及非负数
+    fn decode_length(&mut self) -> DecodeFuture<'_, i64> {
+        Box::pin(async move {
+            let s = self.decode_string().await?;
+            parse_length(&s)
+        })
+    }
+
+    /// 解析Set、Push等类型中的元素
+    fn decode_elements(&mut self) -> DecodeFuture<'_, Vec<Resp>> {
+        Box::pin(async move {
+            let len = self.decode_length().await?;
+            let mut elements = Vec::with_capacity(cmp::max(len, 0) as usize);
+            for _ in 0..len {
+                elements.push(self.decode_resp().await?);
+            }
+            Ok(elements)
+        })
+    }
+}
+
+#[cfg(feature = "async-tokio")]
+impl<R: tokio::io::AsyncRead + Unpin + Send + ?Sized> AsyncRespDecode for R {}
+
+fn invalid_data<E: Into<Box<dyn std::error::Error + Send + Sync>>>(err: E) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, err)
+}
+
+fn to_type(b: u8) -> Result<Type> {
+    match b {
+        PLUS => Ok(Type::String),
+        MINUS => Ok(Type::Error),
+        COLON => Ok(Type::Int),
+        DOLLAR => Ok(Type::BulkString),
+        STAR => Ok(Type::Array),
+        UNDERSCORE => Ok(Type::Null),
+        COMMA => Ok(Type::Double),
+        HASH => Ok(Type::Boolean),
+        LEFT_PAREN => Ok(Type::BigNumber),
+        EXCLAMATION => Ok(Type::BlobError),
+        EQUALS => Ok(Type::Verbatim),
+        PERCENT => Ok(Type::Map),
+        TILDE => Ok(Type::Set),
+        GREATER => Ok(Type::Push),
+        _ => Err(invalid_data(format!("Unexpected Data Type: {}", b))),
+    }
+}
+
+fn parse_length(s: &str) -> Result<i64> {
+    match s.parse::<i64>() {
+        Ok(len) if len >= -1 => Ok(len),
+        _ => Err(invalid_data(format!("Invalid length: {}", s))),
+    }
+}
+
+// inf、-inf及nan也能被正确解析
+fn parse_double(s: &str) -> Result<f64> {
+    s.parse::<f64>()
+        .map_err(|_| invalid_data(format!("Expect double, but got {}", s)))
+}
+
+fn parse_boolean(s: &str) -> Result<bool> {
+    match s {
+        "t" => Ok(true),
+        "f" => Ok(false),
+        _ => Err(invalid_data(format!("Expect t or f, but got {}", s))),
+    }
+}
+
+// 格式: <3字节的格式>:<内容>，如txt:Some string
+fn parse_verbatim(mut bytes: Vec<u8>) -> Result<Resp> {
+    if bytes.len() < 4 || bytes[3] != COLON {
+        return Err(invalid_data("Invalid verbatim string"));
+    }
+    let data = bytes.split_off(4);
+    bytes.truncate(3);
+    Ok(Resp::Verbatim {
+        format: to_string(bytes),
+        data,
+    })
+}
+
 pub enum Type {
     String,
     Error,
     Int,
     BulkString,
     Array,
+    // 以下为RESP3新增的类型
+    Null,
+    Double,
+    Boolean,
+    BigNumber,
+    BlobError,
+    Verbatim,
+    Map,
+    Set,
+    Push,
 }
 
 #[derive(Debug)]
@@ -119,6 +521,22 @@ pub enum Resp {
     Error(String),
     BulkBytes(Vec<u8>),
     Array(Vec<Resp>),
+    /// RESP2中的null bulk string、null array，以及RESP3中的null
+    Null,
+    Double(f64),
+    Boolean(bool),
+    /// 超出i64范围的整数，保留其原始文本
+    BigNumber(String),
+    /// `format`为内容的格式，如`txt`、`mkd`
+    Verbatim {
+        format: String,
+        data: Vec<u8>,
+    },
+    /// 按照master发送的顺序保存键值对
+    Map(Vec<(Resp, Resp)>),
+    Set(Vec<Resp>),
+    /// 带外数据，如RESP3中的Pub/Sub消息
+    Push(Vec<Resp>),
 }
 
 // 回车换行，在redis响应中一般表示终结符，或用作分隔符以分隔数据
@@ -134,11 +552,21 @@ pub(crate) const PLUS: u8 = b'+';
 pub(crate) const MINUS: u8 = b'-';
 // 代表integer响应
 pub(crate) const COLON: u8 = b':';
+// 以下为RESP3新增的类型，依次为null、double、boolean、big number、blob error、verbatim string、map、set及push
+pub(crate) const UNDERSCORE: u8 = b'_';
+pub(crate) const COMMA: u8 = b',';
+pub(crate) const HASH: u8 = b'#';
+pub(crate) const LEFT_PAREN: u8 = b'(';
+pub(crate) const EXCLAMATION: u8 = b'!';
+pub(crate) const EQUALS: u8 = b'=';
+pub(crate) const PERCENT: u8 = b'%';
+pub(crate) const TILDE: u8 = b'~';
+pub(crate) const GREATER: u8 = b'>';
 
 #[cfg(test)]
 mod test {
-    use crate::resp::{Resp, RespDecode};
-    use std::io::Cursor;
+    use crate::resp::{CommandFrame, Resp, RespDecode};
+    use std::io::{Cursor, ErrorKind};
 
     #[test]
     fn test_decode_array() {
@@ -163,4 +591,69 @@ mod test {
             Err(err) => panic!(err),
         }
     }
+
+    #[test]
+    fn test_decode_null() {
+        let mut cursor = Cursor::new(b"$-1\r\n*-1\r\n_\r\n$0\r\n\r\n:1\r\n".to_vec());
+        assert!(matches!(cursor.decode_resp().unwrap(), Resp::Null));
+        assert!(matches!(cursor.decode_resp().unwrap(), Resp::Null));
+        assert!(matches!(cursor.decode_resp().unwrap(), Resp::Null));
+        assert!(matches!(cursor.decode_resp().unwrap(), Resp::BulkBytes(bytes) if bytes.is_empty()));
+        // null之后的数据不受影响
+        assert!(matches!(cursor.decode_resp().unwrap(), Resp::Int(1)));
+    }
+
+    #[test]
+    fn test_decode_resp3() {
+        let input = b"%2\r\n+server\r\n$5\r\nredis\r\n+proto\r\n:3\r\n\
+            ,1.5\r\n,-inf\r\n#t\r\n(3492890328409238509324850943850943825024385\r\n\
+            !10\r\nERR failed\r\n=15\r\ntxt:Some string\r\n~2\r\n:1\r\n:2\r\n\
+            >3\r\n$7\r\nmessage\r\n$2\r\nch\r\n$1\r\nx\r\n";
+        let mut cursor = Cursor::new(input.to_vec());
+        match cursor.decode_resp().unwrap() {
+            Resp::Map(map) => {
+                assert_eq!(2, map.len());
+                assert!(matches!(&map[0], (Resp::String(k), Resp::BulkBytes(v)) if k == "server" && v == b"redis"));
+                assert!(matches!(&map[1], (Resp::String(k), Resp::Int(3)) if k == "proto"));
+            }
+            resp => panic!("wrong type: {:?}", resp),
+        }
+        assert!(matches!(cursor.decode_resp().unwrap(), Resp::Double(d) if d == 1.5));
+        assert!(matches!(cursor.decode_resp().unwrap(), Resp::Double(d) if d == f64::NEG_INFINITY));
+        assert!(matches!(cursor.decode_resp().unwrap(), Resp::Boolean(true)));
+        assert!(matches!(cursor.decode_resp().unwrap(), Resp::BigNumber(n) if n.len() == 43));
+        assert!(matches!(cursor.decode_resp().unwrap(), Resp::Error(err) if err == "ERR failed"));
+        assert!(
+            matches!(cursor.decode_resp().unwrap(), Resp::Verbatim { format, data } if format == "txt" && data == b"Some string")
+        );
+        assert!(matches!(cursor.decode_resp().unwrap(), Resp::Set(set) if set.len() == 2));
+        assert!(matches!(cursor.decode_resp().unwrap(), Resp::Push(push) if push.len() == 3));
+    }
+
+    #[test]
+    fn test_command_frame() {
+        let input = b"\n*3\r\n$3\r\nset\r\n$1\r\na\r\n$0\r\n\r\n*1\r\n$4\r\nPING\r\n";
+        let mut cursor = Cursor::new(input.to_vec());
+        let mut frame = CommandFrame::new();
+        assert_eq!(27, frame.read_from(&mut cursor).unwrap());
+        assert_eq!(3, frame.len());
+        assert_eq!(Some(&b"set"[..]), frame.name());
+        assert_eq!(vec![&b"set"[..], b"a", b""], frame.args().collect::<Vec<_>>());
+        assert_eq!(14, frame.read_from(&mut cursor).unwrap());
+        assert_eq!(vec![&b"PING"[..]], frame.args().collect::<Vec<_>>());
+        assert_eq!(
+            ErrorKind::UnexpectedEof,
+            frame.read_from(&mut cursor).unwrap_err().kind()
+        );
+
+        let mut cursor = Cursor::new(b"+OK\r\n".to_vec());
+        assert_eq!(ErrorKind::InvalidData, frame.read_from(&mut cursor).unwrap_err().kind());
+    }
+
+    #[test]
+    fn test_decode_invalid() {
+        assert!(Cursor::new(b"?\r\n".to_vec()).decode_resp().is_err());
+        assert!(Cursor::new(b"#x\r\n".to_vec()).decode_resp().is_err());
+        assert!(Cursor::new(b"$-2\r\n".to_vec()).decode_resp().is_err());
+    }
 }
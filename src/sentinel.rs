@@ -0,0 +1,332 @@
+/*!
+通过Redis Sentinel发现master，并在failover之后自动切换到新的master
+
+[`SentinelListener`]通过`SENTINEL get-master-addr-by-name`获取master的地址，同时订阅Sentinel的`+switch-master`消息，
+master切换之后，将使用此前的replication id及offset向新的master发起PSYNC。Redis 4.0及以上版本(psync2)的replica
+在晋升为master之后仍会保留原master的replication id，此时可继续进行增量同步，无需重新接收RDB。
+
+[`SentinelListener`]: struct.SentinelListener.html
+*/
+use std::io::{Cursor, Error, ErrorKind, Read, Result};
+use std::net::TcpStream;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, sleep, JoinHandle};
+use std::time::{Duration, Instant};
+
+use log::{info, warn};
+
+use crate::io::{self, send};
+use crate::listener::{Builder, ShutdownHandle};
+use crate::resp::{Resp, RespDecode};
+use crate::{to_string, RedisListener, Stopped};
+
+/// Sentinel相关的配置
+#[derive(Debug, Clone)]
+pub struct SentinelConfig {
+    /// Sentinel的地址列表，将按顺序逐个尝试
+    pub sentinels: Vec<(String, u16)>,
+    /// Sentinel中配置的master名称
+    pub master_name: String,
+    /// Sentinel的用户名
+    pub username: String,
+    /// Sentinel的密码，与Redis的密码相互独立
+    pub password: String,
+    /// 与master的连接中断之后，等待failover完成的最长时间，超过此时间仍未切换到新的master时，`start`将返回错误
+    pub failover_timeout: Duration,
+}
+
+/// 通过Sentinel监听master的事件，master切换之后将自动重连到新的master
+///
+/// `Builder`中的`Config`提供认证、超时、TLS等连接参数，其中的host及port将被Sentinel返回的master地址所替换
+pub struct SentinelListener {
+    sentinel: SentinelConfig,
+    builder: Builder,
+    running: Arc<AtomicBool>,
+}
+
+impl SentinelListener {
+    /// 创建`SentinelListener`
+    ///
+    /// `builder`中必须设置`Config`及控制变量，每次连接master时都会以此构建新的[`Listener`]
+    ///
+    /// [`Listener`]: ../listener/struct.Listener.html
+    pub fn new(sentinel: SentinelConfig, builder: Builder) -> SentinelListener {
+        if builder.config.is_none() {
+            panic!("Parameter Config is required");
+        }
+        let running = match &builder.control_flag {
+            None => panic!("Parameter Control_flag is required"),
+            Some(flag) => Arc::clone(flag),
+        };
+        if sentinel.sentinels.is_empty() {
+            panic!("At least one sentinel is required");
+        }
+        SentinelListener {
+            sentinel,
+            builder,
+            running,
+        }
+    }
+
+    fn is_running(&self) -> bool {
+        self.running.load(Ordering::Relaxed)
+    }
+
+    /// 依次询问各个Sentinel，获取master的地址
+    fn resolve_master(&self) -> Result<(String, u16)> {
+        let mut last_err = Error::new(ErrorKind::NotFound, "No sentinel available");
+        for (host, port) in &self.sentinel.sentinels {
+            match get_master_addr(&self.sentinel, host, *port) {
+                Ok(addr) => return Ok(addr),
+                Err(err) => {
+                    warn!("Sentinel {}:{} 获取master地址失败: {}", host, port, err);
+                    last_err = err;
+                }
+            }
+        }
+        Err(last_err)
+    }
+
+    /// 与master的连接出错之后，等待Sentinel完成failover，返回新的master地址
+    fn wait_failover(&self, current: &(String, u16)) -> Option<(String, u16)> {
+        let deadline = Instant::now() + self.sentinel.failover_timeout;
+        while self.is_running() && Instant::now() < deadline {
+            match self.resolve_master() {
+                Ok(addr) if &addr != current => return Some(addr),
+                Ok(_) => {}
+                Err(err) => warn!("{}", err),
+            }
+            sleep(Duration::from_secs(1));
+        }
+        None
+    }
+}
+
+impl RedisListener for SentinelListener {
+    fn start(&mut self) -> Result<Stopped> {
+        let mut config = self.builder.config.clone().unwrap();
+        let mut master = self.resolve_master()?;
+        loop {
+            info!(
+                "Master {} 的地址为 {}:{}",
+                &self.sentinel.master_name, &master.0, master.1
+            );
+            config.host = master.0.clone();
+            config.port = master.1;
+            // 每个连接使用独立的控制变量，以便master切换时仅中止当前的连接
+            let link_running = Arc::new(AtomicBool::new(self.is_running()));
+            self.builder.with_config(config.clone());
+            self.builder.with_control_flag(Arc::clone(&link_running));
+            let mut listener = self.builder.build();
+
+            let switched = Arc::new(AtomicBool::new(false));
+            let watcher = Watcher::spawn(
+                self.sentinel.clone(),
+                listener.shutdown_handle(),
+                Arc::clone(&self.running),
+                Arc::clone(&switched),
+            );
+            let result = listener.start();
+            watcher.stop();
+            // 保留已同步的进度，切换到新的master之后以此发起PSYNC
+            config.repl_id = listener.config.repl_id.clone();
+            config.repl_offset = listener.config.repl_offset;
+            drop(listener);
+
+            if !self.is_running() {
+                return Ok(Stopped {
+                    repl_id: config.repl_id,
+                    repl_offset: config.repl_offset,
+                });
+            }
+            if switched.load(Ordering::SeqCst) {
+                master = self.resolve_master()?;
+                continue;
+            }
+            match result {
+                Ok(stopped) => return Ok(stopped),
+                Err(err) => {
+                    warn!("与master {}:{} 的连接中断: {}, 等待failover", &master.0, master.1, err);
+                    match self.wait_failover(&master) {
+                        Some(addr) => master = addr,
+                        None if !self.is_running() => {
+                            return Ok(Stopped {
+                                repl_id: config.repl_id,
+                                repl_offset: config.repl_offset,
+                            });
+                        }
+                        None => return Err(err),
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// 在后台订阅`+switch-master`，master切换或外部中止时关闭当前的连接
+struct Watcher {
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Watcher {
+    fn spawn(
+        sentinel: SentinelConfig, handle: ShutdownHandle, running: Arc<AtomicBool>, switched: Arc<AtomicBool>,
+    ) -> Watcher {
+        let stop = Arc::new(AtomicBool::new(false));
+        let stop_flag = Arc::clone(&stop);
+        let thread = thread::Builder::new()
+            .name("sentinel-watcher".to_string())
+            .spawn(move || {
+                let mut index = 0;
+                while !stop_flag.load(Ordering::SeqCst) {
+                    let (host, port) = &sentinel.sentinels[index % sentinel.sentinels.len()];
+                    index += 1;
+                    match subscribe(&sentinel, host, *port) {
+                        Ok(conn) => {
+                            if watch(conn, &sentinel.master_name, &handle, &running, &switched, &stop_flag) {
+                                return;
+                            }
+                        }
+                        Err(err) => warn!("订阅Sentinel {}:{} 失败: {}", host, port, err),
+                    }
+                    // 外部中止时，确保连接被关闭
+                    if !running.load(Ordering::SeqCst) {
+                        handle.shutdown();
+                        return;
+                    }
+                    sleep(Duration::from_secs(1));
+                }
+            })
+            .expect("spawn sentinel watcher failed");
+        Watcher {
+            stop,
+            thread: Some(thread),
+        }
+    }
+
+    fn stop(mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// 读取订阅的消息，返回true表示已关闭连接，无需再订阅
+///
+/// 连接设置了1秒的读超时以便定期检查控制变量，超时可能发生在一条消息的中间，
+/// 因此先将读到的数据缓存起来，只解析其中完整的消息，不完整的部分留待下次读取之后继续解析
+fn watch(
+    mut conn: TcpStream, master_name: &str, handle: &ShutdownHandle, running: &AtomicBool, switched: &AtomicBool,
+    stop: &AtomicBool,
+) -> bool {
+    let mut pending = Vec::new();
+    let mut buf = [0; 4096];
+    loop {
+        if stop.load(Ordering::SeqCst) {
+            return true;
+        }
+        if !running.load(Ordering::SeqCst) {
+            handle.shutdown();
+            return true;
+        }
+        match conn.read(&mut buf) {
+            Ok(0) => {
+                warn!("Sentinel连接中断: Connection closed");
+                return false;
+            }
+            Ok(size) => pending.extend_from_slice(&buf[..size]),
+            Err(ref err) if err.kind() == ErrorKind::WouldBlock || err.kind() == ErrorKind::TimedOut => continue,
+            Err(err) => {
+                warn!("Sentinel连接中断: {}", err);
+                return false;
+            }
+        }
+        loop {
+            let mut cursor = Cursor::new(pending.as_slice());
+            let msg = match cursor.decode_resp() {
+                Ok(msg) => msg,
+                // 消息尚未接收完整
+                Err(ref err) if err.kind() == ErrorKind::UnexpectedEof => break,
+                Err(err) => {
+                    warn!("Sentinel连接中断: {}", err);
+                    return false;
+                }
+            };
+            let consumed = cursor.position() as usize;
+            pending.drain(..consumed);
+            if let Resp::Array(msg) | Resp::Push(msg) = msg {
+                if let Some(payload) = switch_master_payload(msg) {
+                    // 格式: <master name> <old ip> <old port> <new ip> <new port>
+                    let fields: Vec<&str> = payload.split_whitespace().collect();
+                    if fields.len() == 5 && fields[0] == master_name {
+                        info!(
+                            "Master {} 已切换: {}:{} -> {}:{}",
+                            master_name, fields[1], fields[2], fields[3], fields[4]
+                        );
+                        switched.store(true, Ordering::SeqCst);
+                        handle.shutdown();
+                        return true;
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn switch_master_payload(msg: Vec<Resp>) -> Option<String> {
+    let mut iter = msg.into_iter();
+    match (iter.next(), iter.next(), iter.next()) {
+        (Some(Resp::BulkBytes(kind)), Some(Resp::BulkBytes(channel)), Some(Resp::BulkBytes(payload)))
+            if kind.eq_ignore_ascii_case(b"message") && channel == b"+switch-master" =>
+        {
+            Some(to_string(payload))
+        }
+        _ => None,
+    }
+}
+
+/// 连接Sentinel，超时时间为1秒
+fn connect(sentinel: &SentinelConfig, host: &str, port: u16) -> Result<TcpStream> {
+    io::connect(
+        host,
+        port,
+        &sentinel.username,
+        &sentinel.password,
+        Duration::from_secs(1),
+    )
+}
+
+fn get_master_addr(sentinel: &SentinelConfig, host: &str, port: u16) -> Result<(String, u16)> {
+    let mut conn = connect(sentinel, host, port)?;
+    send(
+        &mut conn,
+        b"SENTINEL",
+        &[b"get-master-addr-by-name", sentinel.master_name.as_bytes()],
+    )?;
+    match conn.decode_resp()? {
+        Resp::Array(addr) if addr.len() == 2 => {
+            if let (Resp::BulkBytes(ip), Resp::BulkBytes(port)) = (&addr[0], &addr[1]) {
+                let ip = to_string(ip.clone());
+                if let Ok(port) = to_string(port.clone()).parse::<u16>() {
+                    return Ok((ip, port));
+                }
+            }
+            Err(Error::new(ErrorKind::InvalidData, "Unexpected master address"))
+        }
+        Resp::Error(err) => Err(Error::new(ErrorKind::InvalidData, err)),
+        _ => Err(Error::new(
+            ErrorKind::NotFound,
+            format!("Unknown master {}", &sentinel.master_name),
+        )),
+    }
+}
+
+fn subscribe(sentinel: &SentinelConfig, host: &str, port: u16) -> Result<TcpStream> {
+    let mut conn = connect(sentinel, host, port)?;
+    send(&mut conn, b"SUBSCRIBE", &[b"+switch-master"])?;
+    info!("已订阅Sentinel {}:{} 的+switch-master", host, port);
+    Ok(conn)
+}
@@ -0,0 +1,236 @@
+/*!
+TLS连接的建立，支持native-tls及rustls两种实现，分别由同名的cargo feature开启
+
+两者均开启时默认使用rustls，若设置了PKCS#12格式的`identity`，则使用native-tls
+*/
+#[cfg(feature = "rustls")]
+use std::convert::TryFrom;
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+use std::fs::File;
+#[cfg(feature = "rustls")]
+use std::io::BufReader;
+#[cfg(feature = "native-tls")]
+use std::io::Read;
+use std::io::{Error, ErrorKind, Result};
+use std::net::TcpStream;
+#[cfg(feature = "rustls")]
+use std::sync::Arc;
+
+use crate::config::Config;
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+use crate::transport::TlsTransport;
+use crate::Transport;
+
+/// TLS的具体实现
+pub(crate) enum Backend {
+    #[cfg(feature = "native-tls")]
+    NativeTls,
+    #[cfg(feature = "rustls")]
+    Rustls,
+}
+
+/// 根据开启的feature及`Config`选择TLS的实现
+#[cfg(feature = "rustls")]
+pub(crate) fn backend(config: &Config) -> Result<Backend> {
+    match config.identity {
+        None => Ok(Backend::Rustls),
+        Some(_) => pkcs12_backend(),
+    }
+}
+
+/// 根据开启的feature及`Config`选择TLS的实现
+#[cfg(not(feature = "rustls"))]
+pub(crate) fn backend(_: &Config) -> Result<Backend> {
+    pkcs12_backend()
+}
+
+#[cfg(feature = "native-tls")]
+fn pkcs12_backend() -> Result<Backend> {
+    Ok(Backend::NativeTls)
+}
+
+#[cfg(all(feature = "rustls", not(feature = "native-tls")))]
+fn pkcs12_backend() -> Result<Backend> {
+    Err(Error::new(
+        ErrorKind::Unsupported,
+        "PKCS#12 identity requires the native-tls feature, use tls_cert_file and tls_key_file instead",
+    ))
+}
+
+#[cfg(not(any(feature = "native-tls", feature = "rustls")))]
+fn pkcs12_backend() -> Result<Backend> {
+    Err(Error::new(
+        ErrorKind::Unsupported,
+        "TLS is not supported, enable the native-tls or rustls feature",
+    ))
+}
+
+/// TLS握手时使用的SNI名称，未设置`tls_server_name`时使用host
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+pub(crate) fn server_name(config: &Config) -> &str {
+    config.tls_server_name.as_deref().unwrap_or(&config.host)
+}
+
+/// 在已建立的TCP连接上完成TLS握手
+#[cfg_attr(not(any(feature = "native-tls", feature = "rustls")), allow(unused_variables))]
+pub(crate) fn connect(config: &Config, stream: TcpStream) -> Result<Box<dyn Transport>> {
+    match backend(config)? {
+        #[cfg(feature = "native-tls")]
+        Backend::NativeTls => {
+            let socket = stream.try_clone()?;
+            let tls_stream = native_tls_connector(config)?
+                .connect(server_name(config), stream)
+                .map_err(|err| Error::new(ErrorKind::ConnectionRefused, err.to_string()))?;
+            Ok(Box::new(TlsTransport::new(tls_stream, socket, config.read_timeout)?))
+        }
+        #[cfg(feature = "rustls")]
+        Backend::Rustls => {
+            let socket = stream.try_clone()?;
+            let conn = rustls::ClientConnection::new(rustls_config(config)?, rustls_server_name(config)?)
+                .map_err(|err| Error::new(ErrorKind::InvalidInput, err))?;
+            let mut tls_stream = rustls::StreamOwned::new(conn, stream);
+            // 握手在第一次读写时才会进行，此处提前完成，以便尽早发现证书等问题
+            while tls_stream.conn.is_handshaking() {
+                tls_stream.conn.complete_io(&mut tls_stream.sock)?;
+            }
+            Ok(Box::new(TlsTransport::new(tls_stream, socket, config.read_timeout)?))
+        }
+    }
+}
+
+/// 根据`Config`中的TLS配置创建native-tls的`TlsConnector`
+#[cfg(feature = "native-tls")]
+pub(crate) fn native_tls_connector(config: &Config) -> Result<native_tls::TlsConnector> {
+    let mut builder = native_tls::TlsConnector::builder();
+    builder.danger_accept_invalid_hostnames(config.is_tls_insecure);
+    builder.danger_accept_invalid_certs(config.is_tls_insecure);
+
+    if let Some(ca_file) = &config.tls_ca_file {
+        let pem = read_file(ca_file)?;
+        let pem = String::from_utf8_lossy(&pem);
+        // native-tls每次只解析一个证书，CA文件中可能包含多个
+        for block in pem
+            .split_inclusive(PEM_CERT_END)
+            .filter(|block| block.contains(PEM_CERT_END))
+        {
+            let cert = native_tls::Certificate::from_pem(block.as_bytes()).map_err(invalid_input)?;
+            builder.add_root_certificate(cert);
+        }
+    }
+
+    if let Some(id) = &config.identity {
+        let buff = read_file(id)?;
+        let identity_passwd = match &config.identity_passwd {
+            None => "",
+            Some(passwd) => passwd.as_str(),
+        };
+        let identity = native_tls::Identity::from_pkcs12(&buff, identity_passwd).map_err(invalid_input)?;
+        builder.identity(identity);
+    } else if let (Some(cert_file), Some(key_file)) = (&config.tls_cert_file, &config.tls_key_file) {
+        let identity =
+            native_tls::Identity::from_pkcs8(&read_file(cert_file)?, &read_file(key_file)?).map_err(invalid_input)?;
+        builder.identity(identity);
+    }
+
+    builder.build().map_err(invalid_input)
+}
+
+/// 根据`Config`中的TLS配置创建rustls的`ClientConfig`
+///
+/// 未设置`tls_ca_file`时信任webpki-roots内置的根证书
+#[cfg(feature = "rustls")]
+pub(crate) fn rustls_config(config: &Config) -> Result<Arc<rustls::ClientConfig>> {
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let builder = rustls::ClientConfig::builder_with_provider(Arc::clone(&provider))
+        .with_safe_default_protocol_versions()
+        .map_err(invalid_input)?;
+
+    let builder = if config.is_tls_insecure {
+        builder
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(InsecureVerifier(provider)))
+    } else {
+        let mut roots = rustls::RootCertStore::empty();
+        match &config.tls_ca_file {
+            Some(ca_file) => {
+                for cert in read_pem_certs(ca_file)? {
+                    roots.add(cert).map_err(invalid_input)?;
+                }
+            }
+            None => roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned()),
+        }
+        builder.with_root_certificates(roots)
+    };
+
+    let client_config = match (&config.tls_cert_file, &config.tls_key_file) {
+        (Some(cert_file), Some(key_file)) => {
+            let certs = read_pem_certs(cert_file)?;
+            let key = rustls_pemfile::private_key(&mut BufReader::new(File::open(key_file)?))?
+                .ok_or_else(|| invalid_input(format!("No private key found in {}", key_file)))?;
+            builder.with_client_auth_cert(certs, key).map_err(invalid_input)?
+        }
+        _ => builder.with_no_client_auth(),
+    };
+    Ok(Arc::new(client_config))
+}
+
+#[cfg(feature = "rustls")]
+pub(crate) fn rustls_server_name(config: &Config) -> Result<rustls::pki_types::ServerName<'static>> {
+    rustls::pki_types::ServerName::try_from(server_name(config).to_owned()).map_err(invalid_input)
+}
+
+/// `is_tls_insecure`为true时使用，不校验服务端的证书
+#[cfg(feature = "rustls")]
+#[derive(Debug)]
+struct InsecureVerifier(Arc<rustls::crypto::CryptoProvider>);
+
+#[cfg(feature = "rustls")]
+impl rustls::client::danger::ServerCertVerifier for InsecureVerifier {
+    fn verify_server_cert(
+        &self, _: &rustls::pki_types::CertificateDer<'_>, _: &[rustls::pki_types::CertificateDer<'_>],
+        _: &rustls::pki_types::ServerName<'_>, _: &[u8], _: rustls::pki_types::UnixTime,
+    ) -> std::result::Result<rustls::client::danger::ServerCertVerified, rustls::Error> {
+        Ok(rustls::client::danger::ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self, message: &[u8], cert: &rustls::pki_types::CertificateDer<'_>, dss: &rustls::DigitallySignedStruct,
+    ) -> std::result::Result<rustls::client::danger::HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls12_signature(message, cert, dss, &self.0.signature_verification_algorithms)
+    }
+
+    fn verify_tls13_signature(
+        &self, message: &[u8], cert: &rustls::pki_types::CertificateDer<'_>, dss: &rustls::DigitallySignedStruct,
+    ) -> std::result::Result<rustls::client::danger::HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls13_signature(message, cert, dss, &self.0.signature_verification_algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<rustls::SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+/// 读取PEM文件中的所有证书，返回DER格式
+#[cfg(feature = "rustls")]
+fn read_pem_certs(path: &str) -> Result<Vec<rustls::pki_types::CertificateDer<'static>>> {
+    let certs = rustls_pemfile::certs(&mut BufReader::new(File::open(path)?)).collect::<Result<Vec<_>>>()?;
+    if certs.is_empty() {
+        return Err(invalid_input(format!("No certificate found in {}", path)));
+    }
+    Ok(certs)
+}
+
+#[cfg(feature = "native-tls")]
+const PEM_CERT_END: &str = "-----END CERTIFICATE-----";
+
+#[cfg(feature = "native-tls")]
+fn read_file(path: &str) -> Result<Vec<u8>> {
+    let mut buff = Vec::new();
+    File::open(path)?.read_to_end(&mut buff)?;
+    Ok(buff)
+}
+
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+fn invalid_input<E: Into<Box<dyn std::error::Error + Send + Sync>>>(err: E) -> Error {
+    Error::new(ErrorKind::InvalidInput, err)
+}
@@ -0,0 +1,231 @@
+/*!
+[`Transport`]接口的默认实现
+
+[`Transport`]: ../trait.Transport.html
+*/
+use std::io::{ErrorKind, Read, Result, Write};
+use std::net::{Shutdown, SocketAddr, TcpStream};
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+use std::time::{Duration, Instant};
+
+use crate::io::ChunkReader;
+use crate::Transport;
+
+impl Transport for TcpStream {
+    fn try_clone(&self) -> Result<Box<dyn Transport>> {
+        Ok(Box::new(TcpStream::try_clone(self)?))
+    }
+
+    fn shutdown(&self) -> Result<()> {
+        TcpStream::shutdown(self, Shutdown::Both)
+    }
+
+    fn local_addr(&self) -> Option<SocketAddr> {
+        TcpStream::local_addr(self).ok()
+    }
+}
+
+#[cfg(unix)]
+impl Transport for UnixStream {
+    fn try_clone(&self) -> Result<Box<dyn Transport>> {
+        Ok(Box::new(UnixStream::try_clone(self)?))
+    }
+
+    fn shutdown(&self) -> Result<()> {
+        UnixStream::shutdown(self, Shutdown::Both)
+    }
+}
+
+// TLS连接读取时的轮询间隔，每次轮询之间会释放锁，让心跳线程有机会写入
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+const TLS_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// 可在多个线程之间共享的TLS连接
+///
+/// TLS会话无法像TCP连接那样复制出独立的读写句柄，所以此处使用锁来共享同一会话。
+/// 为了不让阻塞中的读取一直占着锁，底层socket的读超时被设置为一个较短的轮询间隔，
+/// 超时后释放锁并重试，直到读取到数据或超过`Config`中设置的读超时。
+///
+/// `S`为native-tls或rustls的TLS会话，`socket`为其底层TCP连接的句柄
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+pub(crate) struct TlsTransport<S> {
+    stream: Arc<Mutex<S>>,
+    socket: TcpStream,
+    // 正在等待写入的线程数，读取方在重新获取锁之前会先让出
+    pending_writes: Arc<AtomicUsize>,
+    read_timeout: Option<Duration>,
+}
+
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+impl<S: Read + Write + Send + 'static> TlsTransport<S> {
+    pub(crate) fn new(stream: S, socket: TcpStream, read_timeout: Option<Duration>) -> Result<TlsTransport<S>> {
+        let poll_interval = match read_timeout {
+            Some(timeout) if timeout < TLS_POLL_INTERVAL => timeout,
+            _ => TLS_POLL_INTERVAL,
+        };
+        socket.set_read_timeout(Some(poll_interval))?;
+        Ok(TlsTransport {
+            stream: Arc::new(Mutex::new(stream)),
+            socket,
+            pending_writes: Arc::new(AtomicUsize::new(0)),
+            read_timeout,
+        })
+    }
+
+    fn lock_for_write(&self) -> MutexGuard<'_, S> {
+        self.pending_writes.fetch_add(1, Ordering::SeqCst);
+        let guard = self.stream.lock().unwrap();
+        self.pending_writes.fetch_sub(1, Ordering::SeqCst);
+        guard
+    }
+}
+
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+impl<S: Read + Write + Send + 'static> Read for TlsTransport<S> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let start = Instant::now();
+        loop {
+            while self.pending_writes.load(Ordering::SeqCst) > 0 {
+                thread::yield_now();
+            }
+            let result = self.stream.lock().unwrap().read(buf);
+            match result {
+                Err(ref err) if err.kind() == ErrorKind::WouldBlock || err.kind() == ErrorKind::TimedOut => {
+                    if let Some(timeout) = self.read_timeout {
+                        if start.elapsed() >= timeout {
+                            return result;
+                        }
+                    }
+                }
+                _ => return result,
+            }
+        }
+    }
+}
+
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+impl<S: Read + Write + Send + 'static> Write for TlsTransport<S> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        self.lock_for_write().write(buf)
+    }
+
+    // 整条命令在同一次加锁中写完，避免与其它线程写入的数据交错
+    fn write_all(&mut self, buf: &[u8]) -> Result<()> {
+        self.lock_for_write().write_all(buf)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.lock_for_write().flush()
+    }
+}
+
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+impl<S: Read + Write + Send + 'static> Transport for TlsTransport<S> {
+    fn try_clone(&self) -> Result<Box<dyn Transport>> {
+        Ok(Box::new(TlsTransport {
+            stream: Arc::clone(&self.stream),
+            socket: self.socket.try_clone()?,
+            pending_writes: Arc::clone(&self.pending_writes),
+            read_timeout: self.read_timeout,
+        }))
+    }
+
+    fn shutdown(&self) -> Result<()> {
+        self.socket.shutdown(Shutdown::Both)
+    }
+
+    fn local_addr(&self) -> Option<SocketAddr> {
+        self.socket.local_addr().ok()
+    }
+}
+
+/// dual-channel复制时使用的主连接
+///
+/// 接收RDB期间，master在主连接上传播的命令由后台线程持续读取并缓存在内存中，
+/// `BufferedTransport`按顺序返回缓存的数据，写入则直接发送到底层连接
+pub(crate) struct BufferedTransport {
+    inner: Box<dyn Transport>,
+    reader: ChunkReader,
+}
+
+impl BufferedTransport {
+    /// 在后台线程中通过`reader`读取连接，`reader`为`inner`的`try_clone`，
+    /// `buffered`为此前已读入缓冲区但尚未处理的数据，将最先返回
+    pub(crate) fn start(
+        inner: Box<dyn Transport>, mut reader: Box<dyn Transport>, buffered: Vec<u8>,
+    ) -> Result<BufferedTransport> {
+        let (sender, chunks) = mpsc::channel();
+        thread::Builder::new()
+            .name("main-channel-buffer".to_string())
+            .spawn(move || loop {
+                let mut buf = vec![0; 16 * 1024];
+                match reader.read(&mut buf) {
+                    Ok(0) => break,
+                    Ok(size) => {
+                        buf.truncate(size);
+                        if sender.send(Ok(buf)).is_err() {
+                            break;
+                        }
+                    }
+                    Err(err) if err.kind() == ErrorKind::Interrupted => {}
+                    Err(err) => {
+                        let _ = sender.send(Err(err));
+                        break;
+                    }
+                }
+            })?;
+        Ok(BufferedTransport {
+            inner,
+            reader: ChunkReader::new(chunks, buffered),
+        })
+    }
+}
+
+impl Read for BufferedTransport {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        self.reader.read(buf)
+    }
+}
+
+impl Write for BufferedTransport {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        self.inner.write(buf)
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> Result<()> {
+        self.inner.write_all(buf)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl Transport for BufferedTransport {
+    fn try_clone(&self) -> Result<Box<dyn Transport>> {
+        self.inner.try_clone()
+    }
+
+    fn shutdown(&self) -> Result<()> {
+        self.inner.shutdown()
+    }
+
+    fn local_addr(&self) -> Option<SocketAddr> {
+        self.inner.local_addr()
+    }
+}
+
+impl Drop for BufferedTransport {
+    // 后台线程持有连接的另一个句柄，需主动关闭连接才能让它退出
+    fn drop(&mut self) {
+        let _ = self.inner.shutdown();
+    }
+}
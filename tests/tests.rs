@@ -5,7 +5,7 @@ use std::env;
 use std::ops::{Deref, DerefMut};
 use std::process::Command;
 use std::rc::Rc;
-use std::sync::atomic::AtomicBool;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use std::thread;
 use std::thread::sleep;
@@ -17,7 +17,7 @@ use redis::ConnectionAddr;
 use serial_test::serial;
 
 use crate::support::*;
-use redis_event::config::Config;
+use redis_event::config::{Config, ConfigBuilder};
 use redis_event::rdb::{ExpireType, Object};
 use redis_event::{cmd, Event, EventHandler, RedisListener};
 use redis_event::{listener, NoOpEventHandler};
@@ -699,23 +699,11 @@ fn test_aof() {
 
     let t = thread::spawn(move || {
         let cmd_handler = TestCmdHandler { pid, count: rc };
-
-        let ip = String::from("127.0.0.1");
         let conf = Config {
-            is_discard_rdb: false,
             is_aof: true,
-            host: ip,
             port,
             password: String::from("123456"),
-            repl_id: String::from("?"),
-            repl_offset: -1,
-            read_timeout: None,
-            write_timeout: None,
-            is_tls_enabled: false,
-            is_tls_insecure: false,
-            identity: None,
-            username: "".to_string(),
-            identity_passwd: None,
+            ..Config::default()
         };
         let running = Arc::new(AtomicBool::new(true));
 
@@ -801,19 +789,11 @@ fn test_tls() {
     println!("redis-server: {}:{}", host, port);
     let conf = Config {
         is_discard_rdb: true,
-        is_aof: false,
         host: host.to_string(),
         port: *port,
-        password: String::new(),
-        repl_id: String::from("?"),
-        repl_offset: -1,
-        read_timeout: None,
-        write_timeout: None,
         is_tls_enabled: true,
         is_tls_insecure: true,
-        identity: None,
-        username: "".to_string(),
-        identity_passwd: None,
+        ..Config::default()
     };
     let running = Arc::new(AtomicBool::new(true));
 
@@ -832,27 +812,110 @@ fn test_tls() {
     context.stop_server();
 }
 
+#[test]
+#[serial]
+#[ignore = "requires valkey-server 8.0+, set VALKEY_SERVER to its path"]
+fn test_dual_channel_valkey() {
+    let port = 10017;
+    let server = env::var("VALKEY_SERVER").unwrap_or_else(|_| String::from("valkey-server"));
+    let pid = Command::new(server)
+        .arg("--port")
+        .arg(port.to_string())
+        .arg("--save")
+        .arg("")
+        .arg("--repl-diskless-sync")
+        .arg("yes")
+        .arg("--repl-diskless-sync-delay")
+        .arg("0")
+        .arg("--dual-channel-replication-enabled")
+        .arg("yes")
+        .arg("--daemonize")
+        .arg("no")
+        .arg("--loglevel")
+        .arg("warning")
+        .arg("--logfile")
+        .arg(port.to_string())
+        .spawn()
+        .expect("failed to start valkey-server")
+        .id();
+
+    // wait valkey to start
+    sleep(Duration::from_secs(2));
+
+    let client = redis::Client::open(format!("redis://127.0.0.1:{}", port).as_str()).unwrap();
+    let mut conn = client.get_connection().unwrap();
+    let _: () = conn.set("before", "rdb").unwrap();
+
+    struct DualChannelHandler {
+        running: Arc<AtomicBool>,
+        keys: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl EventHandler for DualChannelHandler {
+        fn handle(&mut self, event: Event) {
+            match event {
+                Event::RDB(Object::String(kv)) => {
+                    self.keys
+                        .lock()
+                        .unwrap()
+                        .push(String::from_utf8_lossy(kv.key).to_string());
+                }
+                Event::AOF(cmd::Command::SET(set)) => {
+                    self.keys
+                        .lock()
+                        .unwrap()
+                        .push(String::from_utf8_lossy(set.key).to_string());
+                    if set.key == b"after" {
+                        self.running.store(false, Ordering::SeqCst);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let running = Arc::new(AtomicBool::new(true));
+    let keys = Arc::new(Mutex::new(Vec::new()));
+    let handler = DualChannelHandler {
+        running: running.clone(),
+        keys: keys.clone(),
+    };
+    let t = thread::spawn(move || {
+        let conf = ConfigBuilder::new()
+            .with_port(port)
+            .with_aof(true)
+            .with_dual_channel(true)
+            .build()
+            .unwrap();
+        let mut builder = listener::Builder::new();
+        builder.with_config(conf);
+        builder.with_control_flag(running);
+        builder.with_event_handler(Rc::new(RefCell::new(handler)));
+        let mut redis_listener = builder.build();
+        redis_listener.start()
+    });
+
+    // 等待RDB传输完毕，之后的命令经主连接传播
+    sleep(Duration::from_secs(2));
+    let _: () = conn.set("after", "aof").unwrap();
+    // 唤醒阻塞在读取上的listener，使其检查运行状态
+    let _: () = conn.set("wakeup", "aof").unwrap();
+    let result = t.join().expect("thread error");
+    shutdown_redis(pid);
+
+    result.expect("dual-channel sync failed");
+    let keys = keys.lock().unwrap();
+    assert_eq!(Some("before"), keys.first().map(String::as_str));
+    assert!(keys.iter().any(|key| key == "after"));
+}
+
 fn start_redis_test(rdb: &str, port: u16, rdb_handler: Rc<RefCell<dyn EventHandler>>) {
     let pid = start_redis_server(rdb, port);
     // wait redis to start
     sleep(Duration::from_secs(2));
-
-    let ip = String::from("127.0.0.1");
     let conf = Config {
-        is_discard_rdb: false,
-        is_aof: false,
-        host: ip,
-        port: port,
-        username: "".to_string(),
-        password: String::new(),
-        repl_id: String::from("?"),
-        repl_offset: -1,
-        read_timeout: None,
-        write_timeout: None,
-        is_tls_enabled: false,
-        is_tls_insecure: false,
-        identity: None,
-        identity_passwd: None,
+        port,
+        ..Config::default()
     };
     let running = Arc::new(AtomicBool::new(true));
 